@@ -1,7 +1,11 @@
 mod xac;
+mod xpm;
 mod xsm;
+mod xsmtime;
 
+use crate::xac::xac_convert::convert_actor;
 use crate::xac::xac_parser::xacparse;
+use crate::xac::xac_structs::XacCoordinateSystem;
 use bevy::prelude::*;
 use bevy::render::mesh;
 use bevy::render::mesh::PrimitiveTopology;
@@ -20,8 +24,16 @@ fn setup(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
-    let modeldata =
+    let mut modeldata =
         xacparse("/home/ridwan/IdeaProjects/tos-parser/bg_hi/barrack3/barrack_model.xac");
+    convert_actor(
+        &mut modeldata,
+        XacCoordinateSystem::EMOTIONFX,
+        XacCoordinateSystem {
+            unit_scale: 10.0,
+            ..XacCoordinateSystem::EMOTIONFX
+        },
+    );
 
     let vertices = [
         ([0.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 1.0]),
@@ -55,10 +67,10 @@ fn setup(
 
         for vert in mesh.vertices_attribute {
             for position in vert.mesh_position {
-                pos.push([position.x / 10.0, position.y / 10.0, position.z / 10.0]);
+                pos.push([position.x, position.y, position.z]);
             }
             for normal in vert.mesh_normal {
-                norm.push([normal.x / 10.0, normal.y / 10.0, normal.z / 10.0]);
+                norm.push([normal.x, normal.y, normal.z]);
             }
             for influ in vert.mesh_influence_range_indices {
                 infl.push(influ as u32);
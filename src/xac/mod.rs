@@ -1,3 +1,16 @@
+pub(crate) mod xac_convert;
 pub(crate) mod xac_enums;
+#[cfg(test)]
+pub(crate) mod xac_fixtures;
+pub(crate) mod xac_math;
+pub(crate) mod xac_mesh;
+pub(crate) mod xac_morph;
+pub(crate) mod xac_normals;
+pub(crate) mod xac_obj;
+pub(crate) mod xac_optimize;
 pub(crate) mod xac_parser;
+pub(crate) mod xac_phoneme;
+pub(crate) mod xac_skinning;
 pub(crate) mod xac_structs;
+pub(crate) mod xac_tangent;
+pub(crate) mod xac_transform;
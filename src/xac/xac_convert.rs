@@ -0,0 +1,332 @@
+#![allow(dead_code)]
+
+use bevy::math::{Mat3, Mat4, Quat, Vec3, Vec4};
+
+use crate::xac::xac_enums::XacVerticesAttributeType::{XacBitangentId, XacTangentId};
+use crate::xac::xac_enums::{XacHandedness, XacUpAxis};
+use crate::xac::xac_structs::{
+    Xac, XacCoordinateSystem, XacDeformation, XacMatrix44, XacMorphTargetData, XacNormalOffset,
+    XacPositionOffset, XacQuaternion, XacTangentOffset, XacVec3d, XacVec4d,
+};
+
+impl XacCoordinateSystem {
+    /// Convention the EMotionFX exporter writes: Y up, left handed, file units.
+    pub(crate) const EMOTIONFX: XacCoordinateSystem = XacCoordinateSystem {
+        up_axis: XacUpAxis::Y,
+        handedness: XacHandedness::Left,
+        unit_scale: 1.0,
+    };
+
+    /// Columns are the right, up and forward directions expressed in this system.
+    fn basis(&self) -> Mat3 {
+        let forward = match self.handedness {
+            XacHandedness::Left => 1.0,
+            XacHandedness::Right => -1.0,
+        };
+        match self.up_axis {
+            XacUpAxis::Y => Mat3::from_cols(Vec3::X, Vec3::Y, Vec3::Z * forward),
+            XacUpAxis::Z => Mat3::from_cols(Vec3::X, Vec3::Z, Vec3::NEG_Y * forward),
+        }
+    }
+}
+
+/// Change of basis between two coordinate systems. `unit_scale` is the length of one
+/// unit, so positions are multiplied by `from.unit_scale / to.unit_scale`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct XacConversion {
+    pub(crate) axes: Mat3,
+    pub(crate) scale: f32,
+}
+
+impl XacConversion {
+    pub(crate) fn new(from: XacCoordinateSystem, to: XacCoordinateSystem) -> Self {
+        XacConversion {
+            axes: to.basis() * from.basis().transpose(),
+            scale: from.unit_scale / to.unit_scale,
+        }
+    }
+
+    pub(crate) fn flips_handedness(&self) -> bool {
+        self.axes.determinant() < 0.0
+    }
+
+    pub(crate) fn position(&self, position: Vec3) -> Vec3 {
+        self.axes * position * self.scale
+    }
+
+    pub(crate) fn direction(&self, direction: Vec3) -> Vec3 {
+        self.axes * direction
+    }
+
+    /// Axis aligned scale factors are permuted along with the axes.
+    pub(crate) fn scale(&self, scale: Vec3) -> Vec3 {
+        let axes = self.axes;
+        Mat3::from_cols(axes.x_axis.abs(), axes.y_axis.abs(), axes.z_axis.abs()) * scale
+    }
+
+    pub(crate) fn rotation(&self, rotation: Quat) -> Quat {
+        Quat::from_mat3(&(self.axes * Mat3::from_quat(rotation) * self.axes.transpose()))
+            .normalize()
+    }
+
+    pub(crate) fn matrix(&self, matrix: Mat4) -> Mat4 {
+        let change = Mat4::from_mat3(self.axes * self.scale);
+        change * matrix * change.inverse()
+    }
+
+    fn vec3d(&self, value: &mut XacVec3d) {
+        *value = XacVec3d::from(self.position(Vec3::from(&*value)));
+    }
+
+    fn normal(&self, value: &mut XacVec3d) {
+        *value = XacVec3d::from(self.direction(Vec3::from(&*value)));
+    }
+
+    fn tangent(&self, value: &mut XacVec4d) {
+        let sign = if self.flips_handedness() { -1.0 } else { 1.0 };
+        let direction = self.direction(Vec4::from(&*value).truncate());
+        *value = XacVec4d::from(direction.extend(value.w * sign));
+    }
+
+    fn bi_tangent(&self, value: &mut XacVec4d) {
+        let direction = self.direction(Vec4::from(&*value).truncate());
+        *value = XacVec4d::from(direction.extend(value.w));
+    }
+
+    fn quaternion(&self, value: &mut XacQuaternion) {
+        *value = XacQuaternion::from(self.rotation(Quat::from(&*value)));
+    }
+
+    fn matrix44(&self, value: &mut XacMatrix44) {
+        *value = XacMatrix44::from(self.matrix(Mat4::from(&*value)));
+    }
+
+    /// Position deltas share one value range for all three axes. The range is widened to
+    /// cover axes that change sign, then every delta is quantized again against it.
+    fn deformation(&self, deformation: &mut XacDeformation) {
+        let (min_value, max_value) = (deformation.min_value, deformation.max_value);
+        let mut new_min = min_value * self.scale;
+        let mut new_max = max_value * self.scale;
+        if self.axes.to_cols_array().iter().any(|&value| value < 0.0) {
+            new_min = new_min.min(-new_max);
+            new_max = -new_min;
+        }
+        for offset in deformation.position_offset.iter_mut() {
+            let moved = self.position(offset.dequantize(min_value, max_value));
+            *offset = XacPositionOffset::quantize(moved, new_min, new_max);
+        }
+        deformation.min_value = new_min;
+        deformation.max_value = new_max;
+        for offset in deformation.normal_offset.iter_mut() {
+            *offset = XacNormalOffset::quantize(self.direction(offset.dequantize()));
+        }
+        for offset in deformation.tangent_offset.iter_mut() {
+            *offset = XacTangentOffset::quantize(self.direction(offset.dequantize()));
+        }
+    }
+
+    fn morph_target(&self, target: &mut XacMorphTargetData) {
+        for deformation in target.deformation.iter_mut() {
+            self.deformation(deformation);
+        }
+        for transformation in target.transformation.iter_mut() {
+            self.quaternion(&mut transformation.rotation);
+            self.quaternion(&mut transformation.scale_rotation);
+            self.vec3d(&mut transformation.position);
+            transformation.scale = XacVec3d::from(self.scale(Vec3::from(&transformation.scale)));
+        }
+    }
+}
+
+/// Converts positions, normals, tangents, node transforms and morph targets of an actor
+/// from one coordinate system to another. Triangle winding is flipped when the handedness changes.
+pub(crate) fn convert_actor(xac: &mut Xac, from: XacCoordinateSystem, to: XacCoordinateSystem) {
+    let conversion = XacConversion::new(from, to);
+    for mesh in xac.mesh.iter_mut() {
+        for layer in mesh.vertices_attribute.iter_mut() {
+            layer
+                .mesh_position
+                .iter_mut()
+                .for_each(|value| conversion.vec3d(value));
+            layer
+                .mesh_normal
+                .iter_mut()
+                .for_each(|value| conversion.normal(value));
+            if layer.type_id == XacTangentId as i32 {
+                layer
+                    .mesh_unknown_vec4d
+                    .iter_mut()
+                    .for_each(|value| conversion.tangent(value));
+            }
+            if layer.type_id == XacBitangentId as i32 {
+                layer
+                    .mesh_unknown_vec4d
+                    .iter_mut()
+                    .for_each(|value| conversion.bi_tangent(value));
+            }
+        }
+        for sub in mesh.sub_mesh.iter_mut() {
+            sub.sub_position
+                .iter_mut()
+                .for_each(|value| conversion.vec3d(value));
+            sub.sub_normal
+                .iter_mut()
+                .for_each(|value| conversion.normal(value));
+            sub.sub_tangent
+                .iter_mut()
+                .for_each(|value| conversion.tangent(value));
+            sub.sub_bi_tangent
+                .iter_mut()
+                .for_each(|value| conversion.bi_tangent(value));
+            if conversion.flips_handedness() {
+                for triangle in sub.sub_indices.chunks_exact_mut(3) {
+                    triangle.swap(1, 2);
+                }
+            }
+        }
+    }
+    for node in xac.node_hierarchy.node_data.iter_mut() {
+        conversion.quaternion(&mut node.rotation);
+        conversion.quaternion(&mut node.scale_rotation);
+        conversion.vec3d(&mut node.position);
+        node.scale = XacVec3d::from(conversion.scale(Vec3::from(&node.scale)));
+        conversion.matrix44(&mut node.transform);
+    }
+    for target in xac.morph_target.morph_targets.iter_mut() {
+        conversion.morph_target(target);
+    }
+    xac.metadata.retarget_root_offset *= conversion.scale;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xac::xac_fixtures::{actor, mesh, node, quad, vec3d};
+    use crate::xac::xac_structs::XacTransformation;
+
+    const Z_UP: XacCoordinateSystem = XacCoordinateSystem {
+        up_axis: XacUpAxis::Z,
+        handedness: XacHandedness::Right,
+        unit_scale: 1.0,
+    };
+
+    #[test]
+    fn converts_y_up_actor_to_z_up() {
+        let mut sub = quad(false);
+        sub.sub_position[2] = vec3d(1.0, 2.0, 3.0);
+        sub.sub_normal = (0..4).map(|_| vec3d(0.0, 0.0, 1.0)).collect();
+        let tangent = || XacVec4d {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+            w: 1.0,
+        };
+        sub.sub_tangent = (0..4).map(|_| tangent()).collect();
+        sub.sub_bi_tangent = (0..4).map(|_| tangent()).collect();
+        let mut xac = actor(vec![node(
+            "Bip01",
+            -1,
+            Vec3::new(0.0, 1.0, 0.0),
+            Quat::IDENTITY,
+        )]);
+        xac.mesh.push(mesh(0, sub));
+        convert_actor(&mut xac, XacCoordinateSystem::EMOTIONFX, Z_UP);
+
+        let mesh = &xac.mesh[0];
+        let sub = &mesh.sub_mesh[0];
+        assert_eq!(Vec3::from(&sub.sub_position[2]), Vec3::new(1.0, 3.0, 2.0));
+        assert_eq!(sub.sub_indices, vec![0, 2, 1, 0, 3, 2]);
+        assert_eq!(
+            Vec3::from(&xac.node_hierarchy.node_data[0].position),
+            Vec3::Z
+        );
+        for layer in [&mesh.vertices_attribute[2], &mesh.vertices_attribute[3]] {
+            assert_eq!(layer.mesh_unknown_vec4d.len(), 4);
+            assert_eq!(layer.mesh_unknown_vec4d[0].z, 1.0);
+        }
+        assert_eq!(sub.sub_tangent[0].w, -1.0);
+        assert_eq!(mesh.vertices_attribute[2].mesh_unknown_vec4d[0].w, -1.0);
+        assert_eq!(sub.sub_bi_tangent[0].w, 1.0);
+        assert_eq!(mesh.vertices_attribute[3].mesh_unknown_vec4d[0].w, 1.0);
+        assert_eq!(
+            Vec3::from(&mesh.vertices_attribute[0].mesh_position[2]),
+            Vec3::new(1.0, 3.0, 2.0)
+        );
+    }
+
+    fn target() -> XacMorphTargetData {
+        let offsets = [Vec3::new(0.5, -1.0, 2.0), Vec3::new(-0.25, 0.75, 0.0)];
+        XacMorphTargetData {
+            range_min: 0.0,
+            range_max: 1.0,
+            lod_level: 0,
+            num_deformations: 1,
+            num_transformations: 1,
+            phoneme_set_bitmask: 0,
+            name: "Smile".to_string(),
+            deformation: vec![XacDeformation {
+                node_id: 0,
+                min_value: -1.0,
+                max_value: 2.0,
+                num_vertices: 2,
+                position_offset: offsets
+                    .iter()
+                    .map(|&offset| XacPositionOffset::quantize(offset, -1.0, 2.0))
+                    .collect(),
+                normal_offset: (0..2)
+                    .map(|_| XacNormalOffset::quantize(Vec3::new(0.0, 0.0, 1.0)))
+                    .collect(),
+                tangent_offset: (0..2)
+                    .map(|_| XacTangentOffset::quantize(Vec3::X))
+                    .collect(),
+                vertex_indices: vec![0, 1],
+            }],
+            transformation: vec![XacTransformation {
+                node_id: 0,
+                rotation: XacQuaternion::from(Quat::from_rotation_z(0.5)),
+                scale_rotation: XacQuaternion::from(Quat::IDENTITY),
+                position: XacVec3d::from(Vec3::new(1.0, 2.0, 3.0)),
+                scale: XacVec3d::from(Vec3::new(1.0, 2.0, 3.0)),
+            }],
+        }
+    }
+
+    #[test]
+    fn converts_morph_targets() {
+        let conversion = XacConversion::new(
+            XacCoordinateSystem::EMOTIONFX,
+            XacCoordinateSystem {
+                up_axis: XacUpAxis::Z,
+                handedness: XacHandedness::Right,
+                unit_scale: 0.5,
+            },
+        );
+        let original = target();
+        let mut converted = target();
+        conversion.morph_target(&mut converted);
+
+        let before = &original.deformation[0];
+        let after = &converted.deformation[0];
+        let step = (after.max_value - after.min_value) / 65535.0;
+        for (old, new) in before
+            .position_offset
+            .iter()
+            .zip(after.position_offset.iter())
+        {
+            let expected = conversion.position(old.dequantize(before.min_value, before.max_value));
+            let actual = new.dequantize(after.min_value, after.max_value);
+            assert!(expected.abs_diff_eq(actual, step));
+        }
+        let normal = after.normal_offset[0].dequantize();
+        let expected = conversion.direction(before.normal_offset[0].dequantize());
+        assert!(normal.abs_diff_eq(expected, 1e-6));
+
+        let transformation = &converted.transformation[0];
+        let position = Vec3::from(&transformation.position);
+        assert!(position.abs_diff_eq(conversion.position(Vec3::new(1.0, 2.0, 3.0)), 1e-5));
+        let rotation = Quat::from(&transformation.rotation);
+        let expected = conversion.rotation(Quat::from_rotation_z(0.5));
+        assert!(rotation.abs_diff_eq(expected, 1e-5) || rotation.abs_diff_eq(-expected, 1e-5));
+        assert_eq!(Vec3::from(&transformation.scale), Vec3::new(1.0, 3.0, 2.0));
+    }
+}
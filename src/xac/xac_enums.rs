@@ -1,4 +1,7 @@
 #![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+
 pub(crate) enum XacChunkType {
     XacMeshId = 1,
     XacSkinningId = 2,
@@ -18,8 +21,10 @@ pub(crate) enum XacVerticesAttributeType {
     XacColor32Id = 4,
     XacInfluenceRangeId = 5,
     XacColor128Id = 6,
+    XacBitangentId = 7,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub(crate) enum XacPhonemeType {
     XacNeutralId = 0x1,
     XacMBPXId = 0x2,
@@ -34,3 +39,27 @@ pub(crate) enum XacPhonemeType {
     XacWId = 0x400,
     XacRERId = 0x800,
 }
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum XacNormalMode {
+    Smooth { angle_threshold: f32 },
+    Flat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum XacUpAxis {
+    Y,
+    Z,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum XacHandedness {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum XacMultiplyOrder {
+    ScaleRotTrans = 0,
+    RotScaleTrans = 1,
+}
@@ -0,0 +1,215 @@
+//! Synthetic meshes and actors shared by the xac tests.
+
+use bevy::math::{Mat4, Quat, Vec3};
+
+use crate::xac::xac_enums::XacVerticesAttributeType::{
+    self, XacBitangentId, XacInfluenceRangeId, XacNormalId, XacPositionId, XacTangentId,
+    XacUVCoordId,
+};
+use crate::xac::xac_structs::{
+    Xac, XacActorMaterial, XacActorMesh, XacActorMorphTarget, XacActorNode, XacHeader,
+    XacMaterialTotal, XacMetadata, XacNodeHierarchy, XacQuaternion, XacSubMesh, XacVec2d, XacVec3d,
+    XacVec4d, XacVerticesAttribute,
+};
+
+pub(crate) fn vec3d(x: f32, y: f32, z: f32) -> XacVec3d {
+    XacVec3d { x, y, z }
+}
+
+fn zero4() -> XacVec4d {
+    XacVec4d {
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+        w: 0.0,
+    }
+}
+
+/// A node with unit scale placed at `position` with `rotation` relative to its parent.
+pub(crate) fn node(
+    name: &str,
+    parent_node_id: i32,
+    position: Vec3,
+    rotation: Quat,
+) -> XacActorNode {
+    XacActorNode {
+        rotation: XacQuaternion::from(rotation),
+        scale_rotation: XacQuaternion::from(Quat::IDENTITY),
+        position: XacVec3d::from(position),
+        scale: vec3d(1.0, 1.0, 1.0),
+        parent_node_id,
+        num_child_nodes: 0,
+        include_bounds_calc: 1,
+        transform: (Mat4::from_rotation_translation(rotation, position)).into(),
+        importance_factor: 1.0,
+        name: name.to_string(),
+        parent: None,
+        children: vec![],
+    }
+}
+
+/// An actor with the given nodes and nothing else.
+pub(crate) fn actor(nodes: Vec<XacActorNode>) -> Xac {
+    Xac {
+        header: XacHeader {
+            magic: "XAC ".to_string(),
+            major_version: 1,
+            minor_version: 0,
+            big_endian: 0,
+            multiply_order: 0,
+        },
+        metadata: XacMetadata {
+            reposition_mask: 0,
+            repositioning_node: 0,
+            exporter_major_version: 1,
+            exporter_minor_version: 0,
+            retarget_root_offset: 0.0,
+            source_app: "".to_string(),
+            original_filename: "".to_string(),
+            export_date: "".to_string(),
+            actor_name: "actor".to_string(),
+        },
+        node_hierarchy: XacNodeHierarchy {
+            num_nodes: nodes.len() as i32,
+            num_root_nodes: nodes.iter().filter(|node| node.parent_node_id < 0).count() as i32,
+            node_data: nodes,
+        },
+        material_total: XacMaterialTotal {
+            num_total_materials: 0,
+            num_standard_materials: 0,
+            num_fx_materials: 0,
+        },
+        material_definition: XacActorMaterial {
+            ambient_color: zero4(),
+            diffuse_color: zero4(),
+            specular_color: zero4(),
+            emissive_color: zero4(),
+            shine: 0.0,
+            shine_strength: 0.0,
+            opacity: 1.0,
+            ior: 0.0,
+            double_sided: 0,
+            wireframe: 0,
+            num_layers: 0,
+            name: "".to_string(),
+            layers: vec![],
+        },
+        mesh: vec![],
+        skinning: vec![],
+        shader_material: vec![],
+        morph_target: XacActorMorphTarget {
+            num_morph_targets: 0,
+            lod_morph_target_id: 0,
+            morph_targets: vec![],
+        },
+        indices: vec![],
+        indices_num: 0,
+    }
+}
+
+/// A submesh with only positions and indices. Every other layer is empty.
+pub(crate) fn sub_mesh(position: Vec<XacVec3d>, indices: Vec<u32>) -> XacSubMesh {
+    XacSubMesh {
+        num_indices: indices.len() as i32,
+        num_vertices: position.len() as i32,
+        material_id: 0,
+        num_bones: 0,
+        sub_position: position,
+        sub_normal: vec![],
+        sub_tangent: vec![],
+        sub_bi_tangent: vec![],
+        sub_uv_set: vec![],
+        sub_influence_range_indices: vec![],
+        sub_indices: indices,
+        sub_color: vec![],
+        sub_color8: vec![],
+    }
+}
+
+/// A unit quad in the XY plane facing +Z, with UVs equal to XY. When `duplicated` each
+/// triangle has its own three vertices, as exporters often write them.
+pub(crate) fn quad(duplicated: bool) -> XacSubMesh {
+    let corners = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+    let (order, indices): (Vec<usize>, Vec<u32>) = if duplicated {
+        (vec![0, 1, 2, 0, 2, 3], (0..6).collect())
+    } else {
+        (vec![0, 1, 2, 3], vec![0, 1, 2, 0, 2, 3])
+    };
+    let mut sub = sub_mesh(
+        order
+            .iter()
+            .map(|&corner| vec3d(corners[corner].0, corners[corner].1, 0.0))
+            .collect(),
+        indices,
+    );
+    sub.sub_uv_set = vec![order
+        .iter()
+        .map(|&corner| XacVec2d {
+            x: corners[corner].0,
+            y: corners[corner].1,
+        })
+        .collect()];
+    sub.sub_influence_range_indices = order.iter().map(|&corner| corner as i32).collect();
+    sub
+}
+
+fn layer(type_id: XacVerticesAttributeType) -> XacVerticesAttribute {
+    XacVerticesAttribute {
+        type_id: type_id as i32,
+        attribute_size: 0,
+        keep_originals: 0,
+        scale_factor: 0,
+        mesh_position: vec![],
+        mesh_normal: vec![],
+        mesh_unknown_vec4d: vec![],
+        mesh_unknown_vec2d: vec![],
+        mesh_color8: vec![],
+        mesh_influence_range_indices: vec![],
+        mesh_color: vec![],
+        mesh_data: vec![],
+    }
+}
+
+/// A mesh holding one submesh, with a mesh level layer for every array the submesh has.
+pub(crate) fn mesh(node_id: i32, sub: XacSubMesh) -> XacActorMesh {
+    let mut layers = vec![];
+    let mut position = layer(XacPositionId);
+    position.mesh_position = sub.sub_position.clone();
+    layers.push(position);
+    if !sub.sub_normal.is_empty() {
+        let mut normal = layer(XacNormalId);
+        normal.mesh_normal = sub.sub_normal.clone();
+        layers.push(normal);
+    }
+    if !sub.sub_tangent.is_empty() {
+        let mut tangent = layer(XacTangentId);
+        tangent.mesh_unknown_vec4d = sub.sub_tangent.clone();
+        layers.push(tangent);
+    }
+    if !sub.sub_bi_tangent.is_empty() {
+        let mut bi_tangent = layer(XacBitangentId);
+        bi_tangent.mesh_unknown_vec4d = sub.sub_bi_tangent.clone();
+        layers.push(bi_tangent);
+    }
+    for uv_set in sub.sub_uv_set.iter() {
+        let mut uv = layer(XacUVCoordId);
+        uv.mesh_unknown_vec2d = uv_set.clone();
+        layers.push(uv);
+    }
+    if !sub.sub_influence_range_indices.is_empty() {
+        let mut influence = layer(XacInfluenceRangeId);
+        influence.mesh_influence_range_indices = sub.sub_influence_range_indices.clone();
+        layers.push(influence);
+    }
+    XacActorMesh {
+        node_id,
+        num_influence_ranges: sub.num_vertices,
+        num_vertices: sub.num_vertices,
+        num_indices: sub.num_indices,
+        num_submeshes: 1,
+        num_attribute_layers: layers.len() as i32,
+        collision_mesh: 0,
+        vertices_attribute: layers,
+        sub_mesh: vec![sub],
+    }
+}
@@ -0,0 +1,92 @@
+#![allow(dead_code)]
+
+use bevy::math::{Mat4, Quat, Vec2, Vec3, Vec4};
+
+use crate::xac::xac_structs::{XacMatrix44, XacQuaternion, XacVec2d, XacVec3d, XacVec4d};
+
+impl From<&XacVec2d> for Vec2 {
+    fn from(value: &XacVec2d) -> Self {
+        Vec2::new(value.x, value.y)
+    }
+}
+
+impl From<Vec2> for XacVec2d {
+    fn from(value: Vec2) -> Self {
+        XacVec2d {
+            x: value.x,
+            y: value.y,
+        }
+    }
+}
+
+impl From<&XacVec3d> for Vec3 {
+    fn from(value: &XacVec3d) -> Self {
+        Vec3::new(value.x, value.y, value.z)
+    }
+}
+
+impl From<Vec3> for XacVec3d {
+    fn from(value: Vec3) -> Self {
+        XacVec3d {
+            x: value.x,
+            y: value.y,
+            z: value.z,
+        }
+    }
+}
+
+impl From<&XacVec4d> for Vec4 {
+    fn from(value: &XacVec4d) -> Self {
+        Vec4::new(value.x, value.y, value.z, value.w)
+    }
+}
+
+impl From<Vec4> for XacVec4d {
+    fn from(value: Vec4) -> Self {
+        XacVec4d {
+            x: value.x,
+            y: value.y,
+            z: value.z,
+            w: value.w,
+        }
+    }
+}
+
+impl From<&XacQuaternion> for Quat {
+    fn from(value: &XacQuaternion) -> Self {
+        Quat::from_xyzw(value.x, value.y, value.z, value.w)
+    }
+}
+
+impl From<Quat> for XacQuaternion {
+    fn from(value: Quat) -> Self {
+        XacQuaternion {
+            x: value.x,
+            y: value.y,
+            z: value.z,
+            w: value.w,
+        }
+    }
+}
+
+impl From<&XacMatrix44> for Mat4 {
+    fn from(value: &XacMatrix44) -> Self {
+        Mat4::from_cols(
+            Vec4::from(&value.col1),
+            Vec4::from(&value.col2),
+            Vec4::from(&value.col3),
+            Vec4::from(&value.pos),
+        )
+    }
+}
+
+impl From<Mat4> for XacMatrix44 {
+    fn from(value: Mat4) -> Self {
+        XacMatrix44 {
+            col1: XacVec4d::from(value.x_axis),
+            col2: XacVec4d::from(value.y_axis),
+            col3: XacVec4d::from(value.z_axis),
+            pos: XacVec4d::from(value.w_axis),
+        }
+    }
+}
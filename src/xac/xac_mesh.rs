@@ -0,0 +1,123 @@
+#![allow(dead_code)]
+
+use crate::xac::xac_enums::XacVerticesAttributeType::{
+    XacBitangentId, XacColor128Id, XacColor32Id, XacInfluenceRangeId, XacNormalId, XacPositionId,
+    XacTangentId, XacUVCoordId,
+};
+use crate::xac::xac_structs::{XacActorMesh, XacSubMesh, XacVec4d};
+
+fn pick<T: Clone>(values: &[T], source: &[usize]) -> Vec<T> {
+    if values.is_empty() {
+        return vec![];
+    }
+    source.iter().map(|&index| values[index].clone()).collect()
+}
+
+impl XacSubMesh {
+    /// Rebuilds every per-vertex array so vertex `i` becomes the old vertex `source[i]`.
+    /// Indices are left untouched and must be remapped by the caller.
+    pub(crate) fn select_vertices(&mut self, source: &[usize]) {
+        self.sub_position = pick(&self.sub_position, source);
+        self.sub_normal = pick(&self.sub_normal, source);
+        self.sub_tangent = pick(&self.sub_tangent, source);
+        self.sub_bi_tangent = pick(&self.sub_bi_tangent, source);
+        self.sub_influence_range_indices = pick(&self.sub_influence_range_indices, source);
+        for uv_set in self.sub_uv_set.iter_mut() {
+            *uv_set = pick(uv_set, source);
+        }
+        for color in self.sub_color.iter_mut() {
+            *color = pick(color, source);
+        }
+        for color8 in self.sub_color8.iter_mut() {
+            *color8 = pick(color8, source);
+        }
+        self.num_vertices = source.len() as i32;
+    }
+}
+
+impl XacActorMesh {
+    /// Refreshes the mesh level counters and every vertex layer from the submeshes
+    /// after their vertex data has been rewritten. Layers the submeshes do not carry,
+    /// such as extra tangent sets, are cleared rather than left with a stale vertex count.
+    pub(crate) fn rebuild_layers(&mut self) {
+        self.num_vertices = self.sub_mesh.iter().map(|sub| sub.num_vertices).sum();
+        self.num_indices = self.sub_mesh.iter().map(|sub| sub.num_indices).sum();
+        let mut tangent_layers = 0;
+        let mut bi_tangent_layers = 0;
+        let mut uv_layers = 0;
+        let mut color8_layers = 0;
+        let mut color_layers = 0;
+        for layer in self.vertices_attribute.iter_mut() {
+            layer.mesh_position.clear();
+            layer.mesh_normal.clear();
+            layer.mesh_unknown_vec4d.clear();
+            layer.mesh_unknown_vec2d.clear();
+            layer.mesh_color8.clear();
+            layer.mesh_influence_range_indices.clear();
+            layer.mesh_color.clear();
+            layer.mesh_data.clear();
+            if layer.type_id == XacPositionId as i32 {
+                layer.mesh_position = collect(&self.sub_mesh, |sub| &sub.sub_position);
+            }
+            if layer.type_id == XacNormalId as i32 {
+                layer.mesh_normal = collect(&self.sub_mesh, |sub| &sub.sub_normal);
+            }
+            if layer.type_id == XacTangentId as i32 {
+                if tangent_layers == 0 {
+                    layer.mesh_unknown_vec4d = collect(&self.sub_mesh, |sub| &sub.sub_tangent);
+                }
+                tangent_layers += 1;
+            }
+            if layer.type_id == XacBitangentId as i32 {
+                if bi_tangent_layers == 0 {
+                    layer.mesh_unknown_vec4d = collect(&self.sub_mesh, |sub| &sub.sub_bi_tangent);
+                }
+                bi_tangent_layers += 1;
+            }
+            if layer.type_id == XacUVCoordId as i32 {
+                layer.mesh_unknown_vec2d = self
+                    .sub_mesh
+                    .iter()
+                    .flat_map(|sub| sub.sub_uv_set.get(uv_layers).into_iter().flatten())
+                    .cloned()
+                    .collect();
+                uv_layers += 1;
+            }
+            if layer.type_id == XacColor32Id as i32 {
+                layer.mesh_color8 = self
+                    .sub_mesh
+                    .iter()
+                    .flat_map(|sub| sub.sub_color8.get(color8_layers).into_iter().flatten())
+                    .cloned()
+                    .collect();
+                color8_layers += 1;
+            }
+            if layer.type_id == XacColor128Id as i32 {
+                layer.mesh_color = self
+                    .sub_mesh
+                    .iter()
+                    .flat_map(|sub| sub.sub_color.get(color_layers).into_iter().flatten())
+                    .map(|color| XacVec4d {
+                        x: color.x,
+                        y: color.y,
+                        z: color.z,
+                        w: 1.0,
+                    })
+                    .collect();
+                color_layers += 1;
+            }
+            if layer.type_id == XacInfluenceRangeId as i32 {
+                layer.mesh_influence_range_indices =
+                    collect(&self.sub_mesh, |sub| &sub.sub_influence_range_indices);
+            }
+        }
+    }
+}
+
+/// Concatenates one per-vertex array over all submeshes.
+fn collect<T: Clone>(sub_mesh: &[XacSubMesh], values: impl Fn(&XacSubMesh) -> &Vec<T>) -> Vec<T> {
+    sub_mesh
+        .iter()
+        .flat_map(|sub| values(sub).iter().cloned())
+        .collect()
+}
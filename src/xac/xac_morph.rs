@@ -0,0 +1,230 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use bevy::math::{Mat4, Quat, Vec3};
+
+use crate::xac::xac_structs::{
+    Xac, XacActorNode, XacDeformation, XacDeformedMesh, XacDeformedSubMesh, XacMorphTargetData,
+    XacNormalOffset, XacPositionOffset, XacTangentOffset, XacTransformation, XacVec3d,
+};
+use crate::xac::xac_transform::compose_transform;
+
+impl XacPositionOffset {
+    /// Position deltas are 16 bit fractions of the deformation's value range.
+    pub(crate) fn dequantize(&self, min_value: f32, max_value: f32) -> Vec3 {
+        let range = max_value - min_value;
+        Vec3::new(self.x as f32, self.y as f32, self.z as f32) / 65535.0 * range
+            + Vec3::splat(min_value)
+    }
+
+    pub(crate) fn quantize(offset: Vec3, min_value: f32, max_value: f32) -> Self {
+        let range = max_value - min_value;
+        let fraction = if range > 0.0 {
+            (offset - Vec3::splat(min_value)) / range
+        } else {
+            Vec3::ZERO
+        };
+        let [x, y, z] = (fraction * 65535.0)
+            .round()
+            .clamp(Vec3::ZERO, Vec3::splat(65535.0))
+            .to_array();
+        XacPositionOffset {
+            x: x as u16,
+            y: y as u16,
+            z: z as u16,
+        }
+    }
+}
+
+/// Normal and tangent deltas are 8 bit values mapped to the range -1 to 1.
+fn dequantize_direction(x: u8, y: u8, z: u8) -> Vec3 {
+    Vec3::new(x as f32, y as f32, z as f32) / 127.5 - Vec3::ONE
+}
+
+fn quantize_direction(offset: Vec3) -> [u8; 3] {
+    let [x, y, z] = ((offset + Vec3::ONE) * 127.5)
+        .round()
+        .clamp(Vec3::ZERO, Vec3::splat(255.0))
+        .to_array();
+    [x as u8, y as u8, z as u8]
+}
+
+impl XacNormalOffset {
+    pub(crate) fn dequantize(&self) -> Vec3 {
+        dequantize_direction(self.x, self.y, self.z)
+    }
+
+    pub(crate) fn quantize(offset: Vec3) -> Self {
+        let [x, y, z] = quantize_direction(offset);
+        XacNormalOffset { x, y, z }
+    }
+}
+
+impl XacTangentOffset {
+    pub(crate) fn dequantize(&self) -> Vec3 {
+        dequantize_direction(self.x, self.y, self.z)
+    }
+
+    pub(crate) fn quantize(offset: Vec3) -> Self {
+        let [x, y, z] = quantize_direction(offset);
+        XacTangentOffset { x, y, z }
+    }
+}
+
+impl XacMorphTargetData {
+    /// Clamps a requested weight to the target's range.
+    pub(crate) fn clamp_weight(&self, weight: f32) -> f32 {
+        if self.range_min <= self.range_max {
+            weight.clamp(self.range_min, self.range_max)
+        } else {
+            weight
+        }
+    }
+}
+
+/// Morph targets named in `weights` paired with their clamped, non-zero weights.
+fn active_targets<'a>(
+    xac: &'a Xac,
+    weights: &HashMap<String, f32>,
+) -> Vec<(&'a XacMorphTargetData, f32)> {
+    xac.morph_target
+        .morph_targets
+        .iter()
+        .filter_map(|target| {
+            let weight = target.clamp_weight(*weights.get(&target.name)?);
+            (weight != 0.0).then_some((target, weight))
+        })
+        .collect()
+}
+
+fn apply_deformation(
+    mesh: &mut XacDeformedMesh,
+    vertex_starts: &[usize],
+    deformation: &XacDeformation,
+    weight: f32,
+) {
+    let (min_value, max_value) = (deformation.min_value, deformation.max_value);
+    for (delta, &vertex) in deformation.vertex_indices.iter().enumerate() {
+        let vertex = vertex as usize;
+        let sub_index = vertex_starts.partition_point(|&start| start <= vertex) - 1;
+        let sub = &mut mesh.sub_mesh[sub_index];
+        let local = vertex - vertex_starts[sub_index];
+        if let Some(position) = sub.position.get_mut(local) {
+            if let Some(offset) = deformation.position_offset.get(delta) {
+                let moved =
+                    Vec3::from(&*position) + offset.dequantize(min_value, max_value) * weight;
+                *position = XacVec3d::from(moved);
+            }
+        }
+        if let Some(normal) = sub.normal.get_mut(local) {
+            if let Some(offset) = deformation.normal_offset.get(delta) {
+                let turned = Vec3::from(&*normal) + offset.dequantize() * weight;
+                *normal = XacVec3d::from(turned);
+            }
+        }
+    }
+}
+
+impl Xac {
+    /// Blends the morph targets named in `weights` into the meshes' bind pose positions
+    /// and normals. Vertex numbers of a deformation index the vertices of the mesh on
+    /// the deformation's node, counted across its submeshes.
+    pub(crate) fn morph_meshes(&self, weights: &HashMap<String, f32>) -> Vec<XacDeformedMesh> {
+        let targets = active_targets(self, weights);
+        self.mesh
+            .iter()
+            .map(|mesh| {
+                let mut morphed = XacDeformedMesh {
+                    node_id: mesh.node_id,
+                    sub_mesh: mesh
+                        .sub_mesh
+                        .iter()
+                        .map(|sub| XacDeformedSubMesh {
+                            position: sub.sub_position.clone(),
+                            normal: sub.sub_normal.clone(),
+                        })
+                        .collect(),
+                };
+                if mesh.collision_mesh != 0 || mesh.sub_mesh.is_empty() {
+                    return morphed;
+                }
+                let vertex_starts: Vec<usize> = mesh
+                    .sub_mesh
+                    .iter()
+                    .scan(0, |start, sub| {
+                        let current = *start;
+                        *start += sub.sub_position.len();
+                        Some(current)
+                    })
+                    .collect();
+                for (target, weight) in targets.iter() {
+                    for deformation in target.deformation.iter() {
+                        if deformation.node_id == mesh.node_id {
+                            apply_deformation(&mut morphed, &vertex_starts, deformation, *weight);
+                        }
+                    }
+                }
+                for sub in morphed.sub_mesh.iter_mut() {
+                    for normal in sub.normal.iter_mut() {
+                        *normal = XacVec3d::from(Vec3::from(&*normal).normalize_or_zero());
+                    }
+                }
+                morphed
+            })
+            .collect()
+    }
+
+    /// Local node matrices with the morph targets' transformations blended in. Each
+    /// transformation moves its node from the bind pose towards the stored transform by
+    /// the target's weight.
+    pub(crate) fn morph_node_local_matrices(&self, weights: &HashMap<String, f32>) -> Vec<Mat4> {
+        let order = self.header.multiply_order();
+        let nodes = &self.node_hierarchy.node_data;
+        let mut components: Vec<(Vec3, Quat, Vec3, Quat)> = nodes
+            .iter()
+            .map(|node| {
+                (
+                    Vec3::from(&node.position),
+                    Quat::from(&node.rotation),
+                    Vec3::from(&node.scale),
+                    Quat::from(&node.scale_rotation),
+                )
+            })
+            .collect();
+        for (target, weight) in active_targets(self, weights) {
+            for transformation in target.transformation.iter() {
+                blend_transformation(&mut components, transformation, weight, nodes);
+            }
+        }
+        components
+            .into_iter()
+            .map(|(position, rotation, scale, scale_rotation)| {
+                compose_transform(order, position, rotation, scale, scale_rotation)
+            })
+            .collect()
+    }
+}
+
+fn blend_transformation(
+    components: &mut [(Vec3, Quat, Vec3, Quat)],
+    transformation: &XacTransformation,
+    weight: f32,
+    nodes: &[XacActorNode],
+) {
+    let Ok(node_index) = usize::try_from(transformation.node_id) else {
+        return;
+    };
+    let (Some(component), Some(node)) = (components.get_mut(node_index), nodes.get(node_index))
+    else {
+        return;
+    };
+    let rotation_delta =
+        Quat::from(&node.rotation).inverse() * Quat::from(&transformation.rotation);
+    let scale_rotation_delta =
+        Quat::from(&node.scale_rotation).inverse() * Quat::from(&transformation.scale_rotation);
+    component.0 += (Vec3::from(&transformation.position) - Vec3::from(&node.position)) * weight;
+    component.1 = (component.1 * Quat::IDENTITY.slerp(rotation_delta, weight)).normalize();
+    component.2 += (Vec3::from(&transformation.scale) - Vec3::from(&node.scale)) * weight;
+    component.3 = (component.3 * Quat::IDENTITY.slerp(scale_rotation_delta, weight)).normalize();
+}
@@ -0,0 +1,185 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use bevy::math::Vec3;
+
+use crate::xac::xac_enums::XacNormalMode;
+use crate::xac::xac_enums::XacVerticesAttributeType::XacNormalId;
+use crate::xac::xac_structs::{Xac, XacActorMesh, XacSubMesh, XacVec3d, XacVerticesAttribute};
+
+pub(crate) fn recompute_actor_normals(xac: &mut Xac, mode: XacNormalMode) {
+    for mesh in xac.mesh.iter_mut() {
+        recompute_mesh_normals(mesh, mode);
+    }
+}
+
+/// Rebuilds the normals of every submesh and adds a normal layer when the mesh had none.
+pub(crate) fn recompute_mesh_normals(mesh: &mut XacActorMesh, mode: XacNormalMode) {
+    for sub in mesh.sub_mesh.iter_mut() {
+        recompute_normals(sub, mode);
+    }
+    if !mesh
+        .vertices_attribute
+        .iter()
+        .any(|layer| layer.type_id == XacNormalId as i32)
+    {
+        mesh.vertices_attribute.push(XacVerticesAttribute {
+            type_id: XacNormalId as i32,
+            attribute_size: 12,
+            keep_originals: 1,
+            scale_factor: 0,
+            mesh_position: vec![],
+            mesh_normal: vec![],
+            mesh_unknown_vec4d: vec![],
+            mesh_unknown_vec2d: vec![],
+            mesh_color8: vec![],
+            mesh_influence_range_indices: vec![],
+            mesh_color: vec![],
+            mesh_data: vec![],
+        });
+        mesh.num_attribute_layers = mesh.vertices_attribute.len() as i32;
+    }
+    mesh.rebuild_layers();
+}
+
+/// Rebuilds the vertex normals of a submesh from its triangles.
+///
+/// Smooth normals are area weighted and only average faces whose normals are within
+/// `angle_threshold` radians of each other. Vertices shared by faces that end up with
+/// different normals are split, so flat mode gives every face its own vertices.
+/// Submeshes with indices past their vertex count are left untouched.
+pub(crate) fn recompute_normals(sub: &mut XacSubMesh, mode: XacNormalMode) {
+    let vertex_count = sub.num_vertices as usize;
+    if sub.sub_position.len() != vertex_count
+        || sub
+            .sub_indices
+            .iter()
+            .any(|&index| index as usize >= vertex_count)
+    {
+        return;
+    }
+    let position = |index: u32| Vec3::from(&sub.sub_position[index as usize]);
+    let face_normals: Vec<Vec3> = sub
+        .sub_indices
+        .chunks_exact(3)
+        .map(|triangle| {
+            let a = position(triangle[0]);
+            (position(triangle[1]) - a).cross(position(triangle[2]) - a)
+        })
+        .collect();
+
+    let mut position_groups: HashMap<[u32; 3], usize> = HashMap::new();
+    let mut group_of_vertex = Vec::with_capacity(vertex_count);
+    for position in sub.sub_position.iter() {
+        let key = [
+            position.x.to_bits(),
+            position.y.to_bits(),
+            position.z.to_bits(),
+        ];
+        let next = position_groups.len();
+        group_of_vertex.push(*position_groups.entry(key).or_insert(next));
+    }
+    let mut group_faces: Vec<Vec<usize>> = vec![vec![]; position_groups.len()];
+    for (corner, &index) in sub.sub_indices.iter().enumerate() {
+        let faces = &mut group_faces[group_of_vertex[index as usize]];
+        if !faces.contains(&(corner / 3)) {
+            faces.push(corner / 3);
+        }
+    }
+
+    let mut corner_normals = Vec::with_capacity(sub.sub_indices.len());
+    for (corner, &index) in sub.sub_indices.iter().enumerate() {
+        let face = corner / 3;
+        let face_unit = face_normals[face].normalize_or_zero();
+        let normal = match mode {
+            XacNormalMode::Flat => face_unit,
+            XacNormalMode::Smooth { angle_threshold } => {
+                let min_cos = angle_threshold.cos();
+                group_faces[group_of_vertex[index as usize]]
+                    .iter()
+                    .map(|&other| face_normals[other])
+                    .filter(|other| other.normalize_or_zero().dot(face_unit) >= min_cos)
+                    .sum::<Vec3>()
+                    .normalize_or_zero()
+            }
+        };
+        corner_normals.push(if normal == Vec3::ZERO {
+            face_unit
+        } else {
+            normal
+        });
+    }
+
+    let mut split: HashMap<(u32, [u32; 3]), u32> = HashMap::new();
+    let mut source = Vec::new();
+    let mut normals = Vec::new();
+    let mut indices = Vec::with_capacity(sub.sub_indices.len());
+    for (&index, normal) in sub.sub_indices.iter().zip(corner_normals) {
+        let key = (
+            index,
+            [normal.x.to_bits(), normal.y.to_bits(), normal.z.to_bits()],
+        );
+        let vertex = *split.entry(key).or_insert_with(|| {
+            source.push(index as usize);
+            normals.push(XacVec3d::from(normal));
+            (source.len() - 1) as u32
+        });
+        indices.push(vertex);
+    }
+    sub.select_vertices(&source);
+    sub.sub_normal = normals;
+    sub.sub_indices = indices;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xac::xac_fixtures::{sub_mesh, vec3d};
+
+    fn folded_quad() -> XacSubMesh {
+        sub_mesh(
+            vec![
+                vec3d(0.0, 0.0, 0.0),
+                vec3d(1.0, 0.0, 0.0),
+                vec3d(1.0, 1.0, 0.0),
+                vec3d(0.0, 1.0, 1.0),
+            ],
+            vec![0, 1, 2, 0, 2, 3],
+        )
+    }
+
+    #[test]
+    fn flat_normals_split_shared_vertices() {
+        let mut sub = folded_quad();
+        recompute_normals(&mut sub, XacNormalMode::Flat);
+        assert_eq!(sub.num_vertices, 6);
+        assert_eq!(sub.sub_normal.len(), 6);
+        assert!((Vec3::from(&sub.sub_normal[0]) - Vec3::Z).length() < 1e-6);
+    }
+
+    #[test]
+    fn smooth_normals_keep_shared_vertices() {
+        let mut sub = folded_quad();
+        recompute_normals(
+            &mut sub,
+            XacNormalMode::Smooth {
+                angle_threshold: std::f32::consts::PI,
+            },
+        );
+        assert_eq!(sub.num_vertices, 4);
+        assert!(sub
+            .sub_normal
+            .iter()
+            .all(|normal| (Vec3::from(normal).length() - 1.0).abs() < 1e-5));
+    }
+
+    #[test]
+    fn skips_submesh_with_out_of_range_indices() {
+        let mut sub = folded_quad();
+        sub.sub_indices[5] = 7;
+        recompute_normals(&mut sub, XacNormalMode::Flat);
+        assert_eq!(sub.num_vertices, 4);
+        assert!(sub.sub_normal.is_empty());
+    }
+}
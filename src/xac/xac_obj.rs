@@ -0,0 +1,166 @@
+#![allow(dead_code)]
+
+use std::fs::File;
+use std::io::{BufWriter, Result, Write};
+
+use crate::xac::xac_skinning::deform_actor;
+use crate::xac::xac_structs::{Xac, XacDeformedSubMesh, XacSubMesh};
+use crate::xsm::xsm_structs::Xsm;
+
+/// Frame rate used when the motion does not declare one.
+const DEFAULT_FPS: i32 = 30;
+
+/// Number of `v`, `vt` and `vn` lines written so far. OBJ numbers each kind separately,
+/// so a submesh without UVs or normals must not advance their counts.
+#[derive(Debug, Default)]
+struct ObjOffsets {
+    position: usize,
+    uv: usize,
+    normal: usize,
+}
+
+/// Writes the actor posed by `xsm` at `frame` as a static Wavefront OBJ.
+pub(crate) fn xacwrite_obj(xac: &Xac, xsm: &Xsm, frame: u32, path: &str) -> Result<()> {
+    let obj_file = File::create(path)?;
+    let mut writer = BufWriter::new(obj_file);
+    write_posed_obj(&mut writer, xac, xsm, frame)?;
+    writer.flush()
+}
+
+/// Samples the motion at `frame`, skins every mesh on the CPU and writes one group per
+/// submesh. Collision meshes are left out.
+pub(crate) fn write_posed_obj<W: Write>(
+    writer: &mut W,
+    xac: &Xac,
+    xsm: &Xsm,
+    frame: u32,
+) -> Result<()> {
+    let fps = if xsm.metadata.fps > 0 {
+        xsm.metadata.fps
+    } else {
+        DEFAULT_FPS
+    };
+    let time = frame as f32 / fps as f32;
+    let deformed = deform_actor(xac, xsm, time);
+
+    writeln!(
+        writer,
+        "# {} posed by {}",
+        xac.metadata.actor_name, xsm.metadata.motion_name
+    )?;
+    writeln!(writer, "# frame {} at {} fps ({:.4}s)", frame, fps, time)?;
+    let mut offsets = ObjOffsets::default();
+    for (mesh_index, (mesh, deformed_mesh)) in xac.mesh.iter().zip(deformed.iter()).enumerate() {
+        if mesh.collision_mesh != 0 {
+            continue;
+        }
+        for (sub_index, (sub, deformed_sub)) in mesh
+            .sub_mesh
+            .iter()
+            .zip(deformed_mesh.sub_mesh.iter())
+            .enumerate()
+        {
+            writeln!(writer, "g mesh{}_sub{}", mesh_index, sub_index)?;
+            write_submesh(writer, sub, deformed_sub, &mut offsets)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_submesh<W: Write>(
+    writer: &mut W,
+    sub: &XacSubMesh,
+    deformed_sub: &XacDeformedSubMesh,
+    offsets: &mut ObjOffsets,
+) -> Result<()> {
+    writeln!(writer, "# material {}", sub.material_id)?;
+    for position in deformed_sub.position.iter() {
+        writeln!(writer, "v {} {} {}", position.x, position.y, position.z)?;
+    }
+    let uv_set = sub
+        .sub_uv_set
+        .first()
+        .filter(|uv_set| uv_set.len() == deformed_sub.position.len());
+    let has_uv = uv_set.is_some();
+    if let Some(uv_set) = uv_set {
+        for uv in uv_set.iter() {
+            writeln!(writer, "vt {} {}", uv.x, 1.0 - uv.y)?;
+        }
+    }
+    let has_normal = deformed_sub.normal.len() == deformed_sub.position.len();
+    if has_normal {
+        for normal in deformed_sub.normal.iter() {
+            writeln!(writer, "vn {} {} {}", normal.x, normal.y, normal.z)?;
+        }
+    }
+    for triangle in sub.sub_indices.chunks_exact(3) {
+        write!(writer, "f")?;
+        for &index in triangle {
+            let index = index as usize + 1;
+            let position = index + offsets.position;
+            let uv = index + offsets.uv;
+            let normal = index + offsets.normal;
+            match (has_uv, has_normal) {
+                (true, true) => write!(writer, " {}/{}/{}", position, uv, normal)?,
+                (true, false) => write!(writer, " {}/{}", position, uv)?,
+                (false, true) => write!(writer, " {}//{}", position, normal)?,
+                (false, false) => write!(writer, " {}", position)?,
+            }
+        }
+        writeln!(writer)?;
+    }
+    offsets.position += deformed_sub.position.len();
+    if has_uv {
+        offsets.uv += deformed_sub.position.len();
+    }
+    if has_normal {
+        offsets.normal += deformed_sub.normal.len();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xac::xac_fixtures::{sub_mesh, vec3d};
+    use crate::xac::xac_structs::{XacVec2d, XacVec3d};
+
+    fn triangle(with_uv: bool, with_normal: bool) -> (XacSubMesh, XacDeformedSubMesh) {
+        let position: Vec<XacVec3d> = (0..3)
+            .map(|corner| vec3d(corner as f32, 0.0, 0.0))
+            .collect();
+        let mut sub = sub_mesh(position.clone(), vec![0, 1, 2]);
+        if with_uv {
+            sub.sub_uv_set = vec![(0..3).map(|_| XacVec2d { x: 0.0, y: 0.0 }).collect()];
+        }
+        let normal = if with_normal {
+            (0..3).map(|_| vec3d(0.0, 0.0, 1.0)).collect()
+        } else {
+            vec![]
+        };
+        (sub, XacDeformedSubMesh { position, normal })
+    }
+
+    fn faces(subs: &[(bool, bool)]) -> Vec<String> {
+        let mut bytes = vec![];
+        let mut offsets = ObjOffsets::default();
+        for &(with_uv, with_normal) in subs {
+            let (sub, deformed_sub) = triangle(with_uv, with_normal);
+            write_submesh(&mut bytes, &sub, &deformed_sub, &mut offsets).unwrap();
+        }
+        String::from_utf8(bytes)
+            .unwrap()
+            .lines()
+            .filter(|line| line.starts_with("f "))
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn counts_each_vertex_element_separately() {
+        let lines = faces(&[(false, true), (true, false), (true, true)]);
+        assert_eq!(lines[0], "f 1//1 2//2 3//3");
+        assert_eq!(lines[1], "f 4/1 5/2 6/3");
+        assert_eq!(lines[2], "f 7/4/4 8/5/5 9/6/6");
+    }
+}
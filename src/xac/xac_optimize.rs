@@ -0,0 +1,433 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use crate::xac::xac_structs::{
+    Xac, XacActorMesh, XacOptimizeStats, XacSubMesh, XacVec2d, XacVec3d, XacVec4d,
+};
+
+const CACHE_SIZE: usize = 32;
+
+pub(crate) fn optimize_actor(xac: &mut Xac, weld_tolerance: f32) -> Vec<XacOptimizeStats> {
+    xac.mesh
+        .iter_mut()
+        .map(|mesh| optimize_mesh(mesh, weld_tolerance))
+        .collect()
+}
+
+pub(crate) fn optimize_mesh(mesh: &mut XacActorMesh, weld_tolerance: f32) -> XacOptimizeStats {
+    let mut total = XacOptimizeStats {
+        vertices_before: 0,
+        vertices_after: 0,
+        triangles_before: 0,
+        triangles_after: 0,
+        acmr_before: 0.0,
+        acmr_after: 0.0,
+    };
+    for sub in mesh.sub_mesh.iter_mut() {
+        let stats = optimize_submesh(sub, weld_tolerance);
+        total.vertices_before += stats.vertices_before;
+        total.vertices_after += stats.vertices_after;
+        total.triangles_before += stats.triangles_before;
+        total.triangles_after += stats.triangles_after;
+        total.acmr_before += stats.acmr_before * stats.triangles_before as f32;
+        total.acmr_after += stats.acmr_after * stats.triangles_after as f32;
+    }
+    if total.triangles_before > 0 {
+        total.acmr_before /= total.triangles_before as f32;
+    }
+    if total.triangles_after > 0 {
+        total.acmr_after /= total.triangles_after as f32;
+    }
+    mesh.rebuild_layers();
+    total
+}
+
+/// Welds duplicated vertices of a submesh and reorders its triangles for the
+/// post-transform vertex cache. Vertices end up in first-use order. Submeshes with
+/// indices past their vertex count are left untouched.
+pub(crate) fn optimize_submesh(sub: &mut XacSubMesh, weld_tolerance: f32) -> XacOptimizeStats {
+    let vertices_before = sub.num_vertices as usize;
+    let triangles_before = sub.sub_indices.len() / 3;
+    let acmr_before = average_cache_miss_ratio(&sub.sub_indices, CACHE_SIZE);
+    if sub
+        .sub_indices
+        .iter()
+        .any(|&index| index as usize >= vertices_before)
+    {
+        return XacOptimizeStats {
+            vertices_before,
+            vertices_after: vertices_before,
+            triangles_before,
+            triangles_after: triangles_before,
+            acmr_before,
+            acmr_after: acmr_before,
+        };
+    }
+
+    let remap = weld_vertices(sub, weld_tolerance);
+    let mut indices = Vec::with_capacity(sub.sub_indices.len());
+    for triangle in sub.sub_indices.chunks_exact(3) {
+        let a = remap[triangle[0] as usize];
+        let b = remap[triangle[1] as usize];
+        let c = remap[triangle[2] as usize];
+        if a != b && b != c && a != c {
+            indices.extend_from_slice(&[a, b, c]);
+        }
+    }
+    let vertex_count = remap
+        .iter()
+        .map(|&index| index as usize + 1)
+        .max()
+        .unwrap_or(0);
+    let indices = optimize_vertex_cache(&indices, vertex_count);
+
+    let mut first_use = vec![u32::MAX; vertex_count];
+    let mut order = Vec::new();
+    let mut welded_source = vec![0; vertex_count];
+    for (old, &new) in remap.iter().enumerate().rev() {
+        welded_source[new as usize] = old;
+    }
+    for &index in indices.iter() {
+        if first_use[index as usize] == u32::MAX {
+            first_use[index as usize] = order.len() as u32;
+            order.push(welded_source[index as usize]);
+        }
+    }
+    sub.select_vertices(&order);
+    sub.sub_indices = indices
+        .iter()
+        .map(|&index| first_use[index as usize])
+        .collect();
+    sub.num_indices = sub.sub_indices.len() as i32;
+
+    XacOptimizeStats {
+        vertices_before,
+        vertices_after: sub.num_vertices as usize,
+        triangles_before,
+        triangles_after: sub.sub_indices.len() / 3,
+        acmr_before,
+        acmr_after: average_cache_miss_ratio(&sub.sub_indices, CACHE_SIZE),
+    }
+}
+
+fn close3(a: &XacVec3d, b: &XacVec3d, tolerance: f32) -> bool {
+    (a.x - b.x).abs() <= tolerance
+        && (a.y - b.y).abs() <= tolerance
+        && (a.z - b.z).abs() <= tolerance
+}
+
+fn close4(a: &XacVec4d, b: &XacVec4d, tolerance: f32) -> bool {
+    (a.x - b.x).abs() <= tolerance
+        && (a.y - b.y).abs() <= tolerance
+        && (a.z - b.z).abs() <= tolerance
+        && (a.w - b.w).abs() <= tolerance
+}
+
+fn close2(a: &XacVec2d, b: &XacVec2d, tolerance: f32) -> bool {
+    (a.x - b.x).abs() <= tolerance && (a.y - b.y).abs() <= tolerance
+}
+
+fn same_vertex(sub: &XacSubMesh, a: usize, b: usize, tolerance: f32) -> bool {
+    fn all<T>(values: &[T], a: usize, b: usize, eq: impl Fn(&T, &T) -> bool) -> bool {
+        values.is_empty() || eq(&values[a], &values[b])
+    }
+    all(&sub.sub_position, a, b, |x, y| close3(x, y, tolerance))
+        && all(&sub.sub_normal, a, b, |x, y| close3(x, y, tolerance))
+        && all(&sub.sub_tangent, a, b, |x, y| close4(x, y, tolerance))
+        && all(&sub.sub_bi_tangent, a, b, |x, y| close4(x, y, tolerance))
+        && all(&sub.sub_influence_range_indices, a, b, |x, y| x == y)
+        && sub
+            .sub_uv_set
+            .iter()
+            .all(|uv_set| all(uv_set, a, b, |x, y| close2(x, y, tolerance)))
+        && sub
+            .sub_color
+            .iter()
+            .all(|color| all(color, a, b, |x, y| close3(x, y, tolerance)))
+        && sub
+            .sub_color8
+            .iter()
+            .all(|color8| all(color8, a, b, |x, y| x.x == y.x && x.y == y.y && x.z == y.z))
+}
+
+/// Returns for every vertex the index of the welded vertex it collapses into.
+/// Welded indices are dense and ordered by their first occurrence.
+fn weld_vertices(sub: &XacSubMesh, tolerance: f32) -> Vec<u32> {
+    let vertex_count = sub.num_vertices as usize;
+    let tolerance = tolerance.max(0.0);
+    let cell_size = tolerance.max(f32::EPSILON);
+    let cell_of = |index: usize| -> (i64, i64, i64) {
+        match sub.sub_position.get(index) {
+            Some(position) => (
+                (position.x / cell_size).floor() as i64,
+                (position.y / cell_size).floor() as i64,
+                (position.z / cell_size).floor() as i64,
+            ),
+            None => (0, 0, 0),
+        }
+    };
+
+    let mut grid: HashMap<(i64, i64, i64), Vec<usize>> = HashMap::new();
+    let mut remap = vec![0u32; vertex_count];
+    let mut welded_count = 0u32;
+    for vertex in 0..vertex_count {
+        let (cx, cy, cz) = cell_of(vertex);
+        let mut found = None;
+        'search: for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    if let Some(candidates) = grid.get(&(cx + dx, cy + dy, cz + dz)) {
+                        for &candidate in candidates {
+                            if same_vertex(sub, candidate, vertex, tolerance) {
+                                found = Some(remap[candidate]);
+                                break 'search;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        remap[vertex] = match found {
+            Some(welded) => welded,
+            None => {
+                grid.entry((cx, cy, cz)).or_default().push(vertex);
+                welded_count += 1;
+                welded_count - 1
+            }
+        };
+    }
+    remap
+}
+
+fn vertex_score(cache_position: Option<usize>, remaining_triangles: usize) -> f32 {
+    if remaining_triangles == 0 {
+        return -1.0;
+    }
+    let mut score = 0.0;
+    if let Some(position) = cache_position {
+        if position < 3 {
+            score = 0.75;
+        } else {
+            let scale = 1.0 / (CACHE_SIZE - 3) as f32;
+            score = (1.0 - (position - 3) as f32 * scale).powf(1.5);
+        }
+    }
+    score + 2.0 * (remaining_triangles as f32).powf(-0.5)
+}
+
+/// Tom Forsyth's linear-speed vertex cache optimisation. Triangle winding is kept.
+fn optimize_vertex_cache(indices: &[u32], vertex_count: usize) -> Vec<u32> {
+    let triangle_count = indices.len() / 3;
+    let mut vertex_triangles: Vec<Vec<usize>> = vec![vec![]; vertex_count];
+    for (triangle, corners) in indices.chunks_exact(3).enumerate() {
+        for &corner in corners {
+            vertex_triangles[corner as usize].push(triangle);
+        }
+    }
+    let mut remaining: Vec<usize> = vertex_triangles.iter().map(|list| list.len()).collect();
+    let mut cache_position: Vec<Option<usize>> = vec![None; vertex_count];
+    let mut scores: Vec<f32> = (0..vertex_count)
+        .map(|vertex| vertex_score(None, remaining[vertex]))
+        .collect();
+    let triangle_score = |scores: &[f32], triangle: usize| -> f32 {
+        indices[triangle * 3..triangle * 3 + 3]
+            .iter()
+            .map(|&corner| scores[corner as usize])
+            .sum()
+    };
+
+    let mut emitted = vec![false; triangle_count];
+    let mut output = Vec::with_capacity(indices.len());
+    let mut cache: Vec<u32> = Vec::with_capacity(CACHE_SIZE + 3);
+    let mut cursor = 0;
+    let mut best = (0..triangle_count).max_by(|&a, &b| {
+        triangle_score(&scores, a)
+            .partial_cmp(&triangle_score(&scores, b))
+            .unwrap()
+    });
+
+    while let Some(triangle) = best {
+        emitted[triangle] = true;
+        let corners = &indices[triangle * 3..triangle * 3 + 3];
+        output.extend_from_slice(corners);
+
+        let mut new_cache: Vec<u32> = Vec::with_capacity(CACHE_SIZE + 3);
+        for &corner in corners {
+            remaining[corner as usize] -= 1;
+            if !new_cache.contains(&corner) {
+                new_cache.push(corner);
+            }
+        }
+        for &vertex in cache.iter() {
+            if !new_cache.contains(&vertex) {
+                new_cache.push(vertex);
+            }
+        }
+        for (position, &vertex) in new_cache.iter().enumerate() {
+            let position = if position < CACHE_SIZE {
+                Some(position)
+            } else {
+                None
+            };
+            cache_position[vertex as usize] = position;
+            scores[vertex as usize] = vertex_score(position, remaining[vertex as usize]);
+        }
+        new_cache.truncate(CACHE_SIZE);
+        cache = new_cache;
+
+        best = None;
+        let mut best_score = f32::MIN;
+        for &vertex in cache.iter() {
+            for &candidate in vertex_triangles[vertex as usize].iter() {
+                if !emitted[candidate] {
+                    let score = triangle_score(&scores, candidate);
+                    if score > best_score {
+                        best_score = score;
+                        best = Some(candidate);
+                    }
+                }
+            }
+        }
+        if best.is_none() {
+            while cursor < triangle_count && emitted[cursor] {
+                cursor += 1;
+            }
+            if cursor < triangle_count {
+                best = Some(cursor);
+            }
+        }
+    }
+    output
+}
+
+/// Average number of vertex cache misses per triangle for a FIFO cache.
+pub(crate) fn average_cache_miss_ratio(indices: &[u32], cache_size: usize) -> f32 {
+    let triangle_count = indices.len() / 3;
+    if triangle_count == 0 {
+        return 0.0;
+    }
+    let mut cache: Vec<u32> = Vec::with_capacity(cache_size);
+    let mut misses = 0;
+    for &index in indices.iter() {
+        if !cache.contains(&index) {
+            misses += 1;
+            if cache.len() == cache_size {
+                cache.remove(0);
+            }
+            cache.push(index);
+        }
+    }
+    misses as f32 / triangle_count as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xac::xac_enums::XacVerticesAttributeType;
+    use crate::xac::xac_fixtures::quad;
+    use crate::xac::xac_structs::XacVerticesAttribute;
+
+    /// The quad with a second, lightmap style UV set that shares one texel everywhere.
+    fn lightmapped_quad() -> XacSubMesh {
+        let mut sub = quad(true);
+        let lightmap = sub.sub_uv_set[0]
+            .iter()
+            .map(|_| XacVec2d { x: 0.5, y: 0.5 })
+            .collect();
+        sub.sub_uv_set.push(lightmap);
+        sub
+    }
+
+    #[test]
+    fn welds_duplicated_vertices() {
+        let mut sub = lightmapped_quad();
+        let stats = optimize_submesh(&mut sub, 0.0001);
+        assert_eq!(stats.vertices_before, 6);
+        assert_eq!(stats.vertices_after, 4);
+        assert_eq!(stats.triangles_after, 2);
+        assert_eq!(sub.sub_uv_set[0].len(), 4);
+        assert_eq!(sub.sub_uv_set[1].len(), 4);
+        assert!(sub.sub_indices.iter().all(|&index| index < 4));
+    }
+
+    #[test]
+    fn keeps_vertices_that_differ_in_any_uv_set() {
+        let mut sub = lightmapped_quad();
+        sub.sub_uv_set[0][3].x = 0.25;
+        let stats = optimize_submesh(&mut sub, 0.0001);
+        assert_eq!(stats.vertices_after, 5);
+    }
+
+    fn layer(
+        type_id: XacVerticesAttributeType,
+        values: &[XacVec2d],
+        sub: &XacSubMesh,
+    ) -> XacVerticesAttribute {
+        XacVerticesAttribute {
+            type_id: type_id as i32,
+            attribute_size: 0,
+            keep_originals: 0,
+            scale_factor: 0,
+            mesh_position: sub.sub_position.clone(),
+            mesh_normal: vec![],
+            mesh_unknown_vec4d: vec![],
+            mesh_unknown_vec2d: values.to_vec(),
+            mesh_color8: vec![],
+            mesh_influence_range_indices: vec![],
+            mesh_color: vec![],
+            mesh_data: vec![],
+        }
+    }
+
+    #[test]
+    fn rebuilds_every_vertex_layer() {
+        let sub = lightmapped_quad();
+        let mut mesh = XacActorMesh {
+            node_id: 0,
+            num_influence_ranges: 4,
+            num_vertices: 6,
+            num_indices: 6,
+            num_submeshes: 1,
+            num_attribute_layers: 3,
+            collision_mesh: 0,
+            vertices_attribute: vec![
+                layer(XacVerticesAttributeType::XacPositionId, &[], &sub),
+                layer(
+                    XacVerticesAttributeType::XacUVCoordId,
+                    &sub.sub_uv_set[0],
+                    &sub,
+                ),
+                layer(
+                    XacVerticesAttributeType::XacUVCoordId,
+                    &sub.sub_uv_set[1],
+                    &sub,
+                ),
+            ],
+            sub_mesh: vec![sub],
+        };
+        optimize_mesh(&mut mesh, 0.0001);
+        assert_eq!(mesh.num_vertices, 4);
+        assert_eq!(mesh.vertices_attribute[0].mesh_position.len(), 4);
+        let layers = &mesh.vertices_attribute;
+        assert_eq!(layers[1].mesh_unknown_vec2d.len(), 4);
+        for (uv, position) in layers[1]
+            .mesh_unknown_vec2d
+            .iter()
+            .zip(mesh.sub_mesh[0].sub_position.iter())
+        {
+            assert_eq!((uv.x, uv.y), (position.x, position.y));
+        }
+        assert_eq!(mesh.vertices_attribute[2].mesh_unknown_vec2d.len(), 4);
+        assert_eq!(mesh.vertices_attribute[2].mesh_unknown_vec2d[0].x, 0.5);
+    }
+
+    #[test]
+    fn skips_submesh_with_out_of_range_indices() {
+        let mut sub = quad(false);
+        sub.sub_indices[4] = 9;
+        let stats = optimize_submesh(&mut sub, 0.0001);
+        assert_eq!(stats.vertices_after, 4);
+        assert_eq!(sub.sub_indices, vec![0, 1, 2, 0, 9, 3]);
+    }
+}
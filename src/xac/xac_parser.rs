@@ -10,16 +10,18 @@ use crate::xac::xac_enums::XacChunkType::{
     XacNodeHierarchyId, XacShaderMaterialId, XacSkinningId,
 };
 use crate::xac::xac_enums::XacVerticesAttributeType::{
-    XacColor128Id, XacColor32Id, XacInfluenceRangeId, XacNormalId, XacPositionId, XacTangentId,
-    XacUVCoordId,
+    XacBitangentId, XacColor128Id, XacColor32Id, XacInfluenceRangeId, XacNormalId, XacPositionId,
+    XacTangentId, XacUVCoordId,
 };
 use crate::xac::xac_structs::{
     Xac, XacActorMaterial, XacActorMaterialLayer, XacActorMesh, XacActorMorphTarget, XacActorNode,
-    XacBoolProperties, XacChunk, XacColor8, XacFloatProperties, XacHeader, XacIntProperties,
-    XacMaterialTotal, XacMatrix44, XacMetadata, XacNodeHierarchy, XacQuaternion, XacShaderMaterial,
-    XacSkinning, XacStringProperties, XacSubMesh, XacVec2d, XacVec3d, XacVec4d,
-    XacVerticesAttribute,
+    XacBoolProperties, XacChunk, XacColor8, XacDeformation, XacFloatProperties, XacHeader,
+    XacInfluenceData, XacInfluenceRange, XacIntProperties, XacMaterialTotal, XacMatrix44,
+    XacMetadata, XacMorphTargetData, XacNodeHierarchy, XacNormalOffset, XacPositionOffset,
+    XacQuaternion, XacShaderMaterial, XacSkinning, XacStringProperties, XacSubMesh,
+    XacTangentOffset, XacTransformation, XacVec2d, XacVec3d, XacVec4d, XacVerticesAttribute,
 };
+use crate::xac::xac_tangent::generate_tangents;
 
 pub fn xacparse(path: &str) -> Xac {
     println!("Path : {}", path);
@@ -33,12 +35,7 @@ pub fn xacparse(path: &str) -> Xac {
             multiply_order: 0,
         },
         mesh: vec![],
-        skinning: XacSkinning {
-            node_id: 0,
-            num_local_bones: 0,
-            num_influences: 0,
-            for_collision_mesh: 0,
-        },
+        skinning: vec![],
         material_definition: XacActorMaterial {
             ambient_color: XacVec4d {
                 x: 0.0,
@@ -155,10 +152,10 @@ fn xac_read_vec4d(file: &mut File) -> XacVec4d {
 
 fn xac_read_quaternion(file: &mut File) -> XacQuaternion {
     let quaternion = XacQuaternion {
-        x: file.read_i32::<LittleEndian>().unwrap(),
-        y: file.read_i32::<LittleEndian>().unwrap(),
-        z: file.read_i32::<LittleEndian>().unwrap(),
-        w: file.read_i32::<LittleEndian>().unwrap(),
+        x: file.read_f32::<LittleEndian>().unwrap(),
+        y: file.read_f32::<LittleEndian>().unwrap(),
+        z: file.read_f32::<LittleEndian>().unwrap(),
+        w: file.read_f32::<LittleEndian>().unwrap(),
     };
     quaternion
 }
@@ -261,10 +258,10 @@ fn read_mesh<'a>(file: &'a mut File, xac: &'a mut Xac) -> &'a mut Xac {
     file.read_u8().unwrap(); //Padding
     file.read_u8().unwrap(); //Padding
 
-    let list: Vec<XacVec3d> = Vec::new(); //XacPositionId
-    let list2: Vec<XacVec3d> = Vec::new();
-    let list3: Vec<XacVec4d> = Vec::new();
-    let list4: Vec<XacVec4d> = Vec::new();
+    let mut list: Vec<XacVec3d> = Vec::new(); //XacPositionId
+    let mut list2: Vec<XacVec3d> = Vec::new();
+    let mut list3: Vec<XacVec4d> = Vec::new();
+    let mut list4: Vec<XacVec4d> = Vec::new();
     let mut list5: Vec<Vec<XacVec2d>> = Vec::new();
     let mut list6: Vec<i32> = Vec::new();
     let mut list7: Vec<Vec<XacVec3d>> = Vec::new();
@@ -295,28 +292,42 @@ fn read_mesh<'a>(file: &'a mut File, xac: &'a mut Xac) -> &'a mut Xac {
             for _ in 0..mesh.num_vertices {
                 vertices_attribute.mesh_position.push(xac_read_vec3d(file))
             }
+            list = vertices_attribute.mesh_position.clone();
         }
         if vertices_attribute.type_id == XacNormalId as i32 {
             for _ in 0..mesh.num_vertices {
                 vertices_attribute.mesh_normal.push(xac_read_vec3d(file))
             }
+            list2 = vertices_attribute.mesh_normal.clone();
         }
         if vertices_attribute.type_id == XacTangentId as i32 {
-            let mut list9: Vec<XacVec4d> = Vec::new();
+            for _ in 0..mesh.num_vertices {
+                vertices_attribute
+                    .mesh_unknown_vec4d
+                    .push(xac_read_vec4d(file));
+            }
+            // Further tangent layers belong to additional UV sets and are not used.
             if list3.is_empty() {
-                let temp = &list3;
-                list9 = temp.to_vec();
-            } else {
-                if !list4.is_empty() {
-                    for _ in 0..(&vertices_attribute.attribute_size * &mesh.num_vertices) {
-                        file.read_u8().unwrap();
-                    }
-                    let temp = &list4;
-                    list9 = temp.to_vec();
-                }
+                list3 = vertices_attribute.mesh_unknown_vec4d.clone();
             }
+        }
+        if vertices_attribute.type_id == XacBitangentId as i32 {
             for _ in 0..mesh.num_vertices {
-                list9.push(xac_read_vec4d(file));
+                let bi_tangent = if vertices_attribute.attribute_size == 16 {
+                    xac_read_vec4d(file)
+                } else {
+                    let direction = xac_read_vec3d(file);
+                    XacVec4d {
+                        x: direction.x,
+                        y: direction.y,
+                        z: direction.z,
+                        w: 1.0,
+                    }
+                };
+                vertices_attribute.mesh_unknown_vec4d.push(bi_tangent);
+            }
+            if list4.is_empty() {
+                list4 = vertices_attribute.mesh_unknown_vec4d.clone();
             }
         }
         if vertices_attribute.type_id == XacUVCoordId as i32 {
@@ -365,6 +376,7 @@ fn read_mesh<'a>(file: &'a mut File, xac: &'a mut Xac) -> &'a mut Xac {
             sub_bi_tangent: vec![],
             sub_uv_set: vec![],
             sub_influence_range_indices: vec![],
+            sub_indices: vec![],
             sub_color: vec![],
             sub_color8: vec![],
         };
@@ -375,45 +387,46 @@ fn read_mesh<'a>(file: &'a mut File, xac: &'a mut Xac) -> &'a mut Xac {
 
         if !list.is_empty() {
             let temp = &list;
-            submeshes.sub_position = temp[num8..submeshes.num_vertices.clone() as usize].to_vec();
+            submeshes.sub_position = temp[num8..num8 + submeshes.num_vertices as usize].to_vec();
         }
         if !list2.is_empty() {
             let temp = &list2;
-            submeshes.sub_normal = temp[num8..submeshes.num_vertices.clone() as usize].to_vec();
+            submeshes.sub_normal = temp[num8..num8 + submeshes.num_vertices as usize].to_vec();
         }
         if !list3.is_empty() {
             let temp = &list3;
-            submeshes.sub_tangent = temp[num8..submeshes.num_vertices.clone() as usize].to_vec();
+            submeshes.sub_tangent = temp[num8..num8 + submeshes.num_vertices as usize].to_vec();
         }
         if !list4.is_empty() {
             let temp = &list4;
-            submeshes.sub_bi_tangent = temp[num8..submeshes.num_vertices.clone() as usize].to_vec();
+            submeshes.sub_bi_tangent = temp[num8..num8 + submeshes.num_vertices as usize].to_vec();
         }
         submeshes.sub_influence_range_indices =
-            list6[num8..submeshes.num_vertices.clone() as usize].to_vec();
+            list6[num8..num8 + submeshes.num_vertices as usize].to_vec();
 
         for i in 0..list8.len() {
             let temp = &list8;
             submeshes
                 .sub_color8
-                .push(temp[i][num8..submeshes.num_vertices.clone() as usize].to_vec());
+                .push(temp[i][num8..num8 + submeshes.num_vertices as usize].to_vec());
         }
         for i in 0..list7.len() {
             let temp = &list7;
             submeshes
                 .sub_color
-                .push(temp[i][num8..submeshes.num_vertices.clone() as usize].to_vec());
+                .push(temp[i][num8..num8 + submeshes.num_vertices as usize].to_vec());
         }
         for i in 0..list5.len() {
             let temp = &list5;
-            submeshes.sub_uv_set = temp[i][num8..submeshes.num_vertices.clone() as usize].to_vec();
+            submeshes
+                .sub_uv_set
+                .push(temp[i][num8..num8 + submeshes.num_vertices as usize].to_vec());
         }
 
         for _ in 0..submeshes.num_indices {
-            let temp = &list8;
             submeshes
-                .sub_influence_range_indices
-                .push(file.read_i32::<LittleEndian>().unwrap());
+                .sub_indices
+                .push(file.read_u32::<LittleEndian>().unwrap());
             xac.indices_num = xac.indices.len() as i32;
             xac.indices.push(xac.indices_num.clone() as u32);
         }
@@ -421,6 +434,9 @@ fn read_mesh<'a>(file: &'a mut File, xac: &'a mut Xac) -> &'a mut Xac {
         for _ in 0..(4 * &submeshes.num_bones) {
             file.read_u8().unwrap();
         }
+        if submeshes.sub_tangent.is_empty() {
+            generate_tangents(&mut submeshes);
+        }
         num8 = num8 + submeshes.num_vertices.clone() as usize;
         num9 = num9 + submeshes.num_indices.clone() as usize;
         mesh.sub_mesh.push(submeshes);
@@ -429,6 +445,48 @@ fn read_mesh<'a>(file: &'a mut File, xac: &'a mut Xac) -> &'a mut Xac {
     xac
 }
 fn read_skinning<'a>(file: &'a mut File, xac: &'a mut Xac) -> &'a mut Xac {
+    let mut skinning = XacSkinning {
+        node_id: 0,
+        num_local_bones: 0,
+        num_influences: 0,
+        for_collision_mesh: 0,
+        influence_data: vec![],
+        influence_range: vec![],
+    };
+    skinning.node_id = file.read_i32::<LittleEndian>().unwrap();
+    skinning.num_local_bones = file.read_i32::<LittleEndian>().unwrap();
+    skinning.num_influences = file.read_i32::<LittleEndian>().unwrap();
+    skinning.for_collision_mesh = file.read_u8().unwrap();
+    file.read_u8().unwrap(); //Padding
+    file.read_u8().unwrap(); //Padding
+    file.read_u8().unwrap(); //Padding
+
+    for _ in 0..skinning.num_influences {
+        let influence = XacInfluenceData {
+            weight: file.read_f32::<LittleEndian>().unwrap(),
+            bone_id: file.read_i16::<LittleEndian>().unwrap(),
+        };
+        file.read_u8().unwrap(); //Padding
+        file.read_u8().unwrap(); //Padding
+        skinning.influence_data.push(influence);
+    }
+
+    // One range per original vertex of the mesh this skinning belongs to.
+    let num_ranges = xac
+        .mesh
+        .iter()
+        .find(|mesh| {
+            mesh.node_id == skinning.node_id && mesh.collision_mesh == skinning.for_collision_mesh
+        })
+        .map(|mesh| mesh.num_influence_ranges)
+        .unwrap_or(0);
+    for _ in 0..num_ranges {
+        skinning.influence_range.push(XacInfluenceRange {
+            first_influence_index: file.read_i32::<LittleEndian>().unwrap(),
+            num_influences: file.read_i32::<LittleEndian>().unwrap(),
+        });
+    }
+    xac.skinning.push(skinning);
     xac
 }
 
@@ -567,16 +625,16 @@ fn read_node_hierarchy<'a>(file: &'a mut File, xac: &'a mut Xac) -> &'a mut Xac
     for _ in 0..xac.node_hierarchy.num_nodes {
         let mut xac_node_data = XacActorNode {
             rotation: XacQuaternion {
-                x: 0,
-                y: 0,
-                z: 0,
-                w: 0,
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+                w: 0.0,
             },
             scale_rotation: XacQuaternion {
-                x: 0,
-                y: 0,
-                z: 0,
-                w: 0,
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+                w: 0.0,
             },
             position: XacVec3d {
                 x: 0.0,
@@ -651,6 +709,74 @@ fn read_node_hierarchy<'a>(file: &'a mut File, xac: &'a mut Xac) -> &'a mut Xac
     xac
 }
 fn read_morph_target<'a>(file: &'a mut File, xac: &'a mut Xac) -> &'a mut Xac {
+    xac.morph_target.num_morph_targets = file.read_i32::<LittleEndian>().unwrap();
+    xac.morph_target.lod_morph_target_id = file.read_i32::<LittleEndian>().unwrap();
+
+    for _ in 0..xac.morph_target.num_morph_targets {
+        let mut morph_target = XacMorphTargetData {
+            range_min: file.read_f32::<LittleEndian>().unwrap(),
+            range_max: file.read_f32::<LittleEndian>().unwrap(),
+            lod_level: file.read_i32::<LittleEndian>().unwrap(),
+            num_deformations: file.read_i32::<LittleEndian>().unwrap(),
+            num_transformations: file.read_i32::<LittleEndian>().unwrap(),
+            phoneme_set_bitmask: file.read_i32::<LittleEndian>().unwrap(),
+            name: "".to_string(),
+            deformation: vec![],
+            transformation: vec![],
+        };
+        morph_target.name = xac_read_string(file);
+
+        for _ in 0..morph_target.num_deformations {
+            let mut deformation = XacDeformation {
+                node_id: file.read_i32::<LittleEndian>().unwrap(),
+                min_value: file.read_f32::<LittleEndian>().unwrap(),
+                max_value: file.read_f32::<LittleEndian>().unwrap(),
+                num_vertices: file.read_i32::<LittleEndian>().unwrap(),
+                position_offset: vec![],
+                normal_offset: vec![],
+                tangent_offset: vec![],
+                vertex_indices: vec![],
+            };
+            for _ in 0..deformation.num_vertices {
+                deformation.position_offset.push(XacPositionOffset {
+                    x: file.read_u16::<LittleEndian>().unwrap(),
+                    y: file.read_u16::<LittleEndian>().unwrap(),
+                    z: file.read_u16::<LittleEndian>().unwrap(),
+                });
+            }
+            for _ in 0..deformation.num_vertices {
+                deformation.normal_offset.push(XacNormalOffset {
+                    x: file.read_u8().unwrap(),
+                    y: file.read_u8().unwrap(),
+                    z: file.read_u8().unwrap(),
+                });
+            }
+            for _ in 0..deformation.num_vertices {
+                deformation.tangent_offset.push(XacTangentOffset {
+                    x: file.read_u8().unwrap(),
+                    y: file.read_u8().unwrap(),
+                    z: file.read_u8().unwrap(),
+                });
+            }
+            for _ in 0..deformation.num_vertices {
+                deformation
+                    .vertex_indices
+                    .push(file.read_u32::<LittleEndian>().unwrap());
+            }
+            morph_target.deformation.push(deformation);
+        }
+
+        for _ in 0..morph_target.num_transformations {
+            morph_target.transformation.push(XacTransformation {
+                node_id: file.read_i32::<LittleEndian>().unwrap(),
+                rotation: xac_read_quaternion(file),
+                scale_rotation: xac_read_quaternion(file),
+                position: xac_read_vec3d(file),
+                scale: xac_read_vec3d(file),
+            });
+        }
+        xac.morph_target.morph_targets.push(morph_target);
+    }
     xac
 }
 fn read_material_total<'a>(file: &'a mut File, xac: &'a mut Xac) -> &'a mut Xac {
@@ -0,0 +1,205 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use crate::xac::xac_enums::XacPhonemeType;
+use crate::xac::xac_enums::XacPhonemeType::{
+    XacAAAOOWId, XacAWId, XacFVId, XacIHAEAHEYAYHId, XacIYEHYId, XacLELId, XacMBPXId,
+    XacNNGCHJDHDGTKZZHTHSSHId, XacNeutralId, XacRERId, XacUWUHOYId, XacWId,
+};
+use crate::xac::xac_structs::{
+    Xac, XacMorphCurve, XacMorphCurveKey, XacMorphTargetData, XacTimedPhoneme,
+};
+use crate::xsm::xsm_sample::key_segment;
+
+impl XacPhonemeType {
+    pub(crate) const ALL: [XacPhonemeType; 12] = [
+        XacNeutralId,
+        XacMBPXId,
+        XacAAAOOWId,
+        XacIHAEAHEYAYHId,
+        XacAWId,
+        XacNNGCHJDHDGTKZZHTHSSHId,
+        XacIYEHYId,
+        XacUWUHOYId,
+        XacFVId,
+        XacLELId,
+        XacWId,
+        XacRERId,
+    ];
+
+    /// Phoneme sets whose flag is set in `bitmask`.
+    pub(crate) fn from_bitmask(bitmask: i32) -> Vec<XacPhonemeType> {
+        XacPhonemeType::ALL
+            .into_iter()
+            .filter(|&phoneme| bitmask & phoneme as i32 != 0)
+            .collect()
+    }
+
+    /// Set containing an ARPAbet phoneme such as `AA` or `SH`. Silence maps to neutral.
+    pub(crate) fn from_phoneme(phoneme: &str) -> Option<XacPhonemeType> {
+        let phoneme_set = match phoneme.to_ascii_uppercase().as_str() {
+            "" | "SIL" | "SP" => XacNeutralId,
+            "M" | "B" | "P" | "X" => XacMBPXId,
+            "AA" | "AO" | "OW" => XacAAAOOWId,
+            "IH" | "AE" | "AH" | "EY" | "AY" | "H" => XacIHAEAHEYAYHId,
+            "AW" => XacAWId,
+            "N" | "NG" | "CH" | "J" | "DH" | "D" | "G" | "T" | "K" | "Z" | "ZH" | "TH" | "S"
+            | "SH" => XacNNGCHJDHDGTKZZHTHSSHId,
+            "IY" | "EH" | "Y" => XacIYEHYId,
+            "UW" | "UH" | "OY" => XacUWUHOYId,
+            "F" | "V" => XacFVId,
+            "L" | "EL" => XacLELId,
+            "W" => XacWId,
+            "R" | "ER" => XacRERId,
+            _ => return None,
+        };
+        Some(phoneme_set)
+    }
+}
+
+impl XacMorphTargetData {
+    pub(crate) fn phoneme_sets(&self) -> Vec<XacPhonemeType> {
+        XacPhonemeType::from_bitmask(self.phoneme_set_bitmask)
+    }
+}
+
+impl XacMorphCurve {
+    pub(crate) fn sample(&self, time: f32) -> f32 {
+        if self.keys.is_empty() {
+            return 0.0;
+        }
+        let times: Vec<f32> = self.keys.iter().map(|key| key.time).collect();
+        let (previous, next, factor) = key_segment(&times, time);
+        let (previous, next) = (self.keys[previous].weight, self.keys[next].weight);
+        previous + (next - previous) * factor
+    }
+}
+
+/// Weight a single phoneme contributes at `time`: full between its start and end, with
+/// linear ramps of `blend_time` before and after.
+fn phoneme_weight(phoneme: &XacTimedPhoneme, blend_time: f32, time: f32) -> f32 {
+    if time >= phoneme.start && time <= phoneme.end {
+        1.0
+    } else if blend_time <= 0.0 {
+        0.0
+    } else if time < phoneme.start {
+        (1.0 - (phoneme.start - time) / blend_time).max(0.0)
+    } else {
+        (1.0 - (time - phoneme.end) / blend_time).max(0.0)
+    }
+}
+
+/// Builds one weight curve per phoneme morph target of the actor from a timed phoneme
+/// sequence. A target follows every phoneme in its phoneme sets, taking the strongest
+/// when ramps overlap. Targets that none of the phonemes use get no curve.
+pub(crate) fn lip_sync_curves(
+    xac: &Xac,
+    phonemes: &[XacTimedPhoneme],
+    blend_time: f32,
+) -> Vec<XacMorphCurve> {
+    xac.morph_target
+        .morph_targets
+        .iter()
+        .filter_map(|target| {
+            let sets = target.phoneme_sets();
+            let used: Vec<&XacTimedPhoneme> = phonemes
+                .iter()
+                .filter(|phoneme| sets.contains(&phoneme.phoneme))
+                .collect();
+            if used.is_empty() {
+                return None;
+            }
+            let mut times: Vec<f32> = used
+                .iter()
+                .flat_map(|phoneme| {
+                    [
+                        phoneme.start - blend_time.max(0.0),
+                        phoneme.start,
+                        phoneme.end,
+                        phoneme.end + blend_time.max(0.0),
+                    ]
+                })
+                .map(|time| time.max(0.0))
+                .collect();
+            times.sort_by(f32::total_cmp);
+            times.dedup();
+            Some(XacMorphCurve {
+                name: target.name.clone(),
+                keys: times
+                    .into_iter()
+                    .map(|time| XacMorphCurveKey {
+                        time,
+                        weight: used
+                            .iter()
+                            .map(|phoneme| phoneme_weight(phoneme, blend_time, time))
+                            .fold(0.0, f32::max),
+                    })
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+/// Weights of every curve at `time`, keyed by morph target name, ready for
+/// `Xac::morph_meshes`.
+pub(crate) fn sample_curves(curves: &[XacMorphCurve], time: f32) -> HashMap<String, f32> {
+    curves
+        .iter()
+        .map(|curve| (curve.name.clone(), curve.sample(time)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xac::xac_fixtures::actor;
+
+    fn target(name: &str, phoneme_set_bitmask: i32) -> XacMorphTargetData {
+        XacMorphTargetData {
+            range_min: 0.0,
+            range_max: 1.0,
+            lod_level: 0,
+            num_deformations: 0,
+            num_transformations: 0,
+            phoneme_set_bitmask,
+            name: name.to_string(),
+            deformation: vec![],
+            transformation: vec![],
+        }
+    }
+
+    #[test]
+    fn decodes_phoneme_set_bitmasks() {
+        assert!(XacPhonemeType::from_bitmask(0).is_empty());
+        assert_eq!(
+            XacPhonemeType::from_bitmask(0x2 | 0x20),
+            vec![XacMBPXId, XacNNGCHJDHDGTKZZHTHSSHId]
+        );
+        assert_eq!(
+            XacPhonemeType::from_bitmask(0xfff),
+            XacPhonemeType::ALL.to_vec()
+        );
+        assert_eq!(target("Lips", 0x100).phoneme_sets(), vec![XacFVId]);
+    }
+
+    #[test]
+    fn curves_ramp_in_and_out_around_each_phoneme() {
+        let mut xac = actor(vec![]);
+        xac.morph_target.morph_targets = vec![target("Closed", 0x2), target("Open", 0x4)];
+        let phonemes = [XacTimedPhoneme {
+            phoneme: XacPhonemeType::from_phoneme("m").unwrap(),
+            start: 1.0,
+            end: 1.5,
+        }];
+        let curves = lip_sync_curves(&xac, &phonemes, 0.2);
+        assert_eq!(curves.len(), 1);
+        assert_eq!(curves[0].name, "Closed");
+        for (time, weight) in [(0.7, 0.0), (0.9, 0.5), (1.2, 1.0), (1.6, 0.5), (2.0, 0.0)] {
+            assert!((curves[0].sample(time) - weight).abs() < 1e-5);
+        }
+        let weights = sample_curves(&curves, 1.25);
+        assert_eq!(weights.get("Closed"), Some(&1.0));
+        assert!(!weights.contains_key("Open"));
+    }
+}
@@ -0,0 +1,216 @@
+#![allow(dead_code)]
+
+use bevy::math::{Mat3, Mat4, Vec3};
+
+use crate::xac::xac_structs::{
+    Xac, XacActorMesh, XacDeformedMesh, XacDeformedSubMesh, XacSkinning, XacVec3d,
+};
+use crate::xsm::xsm_structs::{Xsm, XsmBindOptions, XsmBinding};
+
+impl Xac {
+    /// Skinning data of a mesh, matched by node and collision flag.
+    pub(crate) fn mesh_skinning(&self, mesh: &XacActorMesh) -> Option<&XacSkinning> {
+        self.skinning.iter().find(|skinning| {
+            skinning.node_id == mesh.node_id && skinning.for_collision_mesh == mesh.collision_mesh
+        })
+    }
+}
+
+fn node_matrix(matrices: &[Mat4], node_id: i32) -> Mat4 {
+    if node_id < 0 {
+        return Mat4::IDENTITY;
+    }
+    matrices
+        .get(node_id as usize)
+        .copied()
+        .unwrap_or(Mat4::IDENTITY)
+}
+
+/// Weighted sum of the skinning matrices influencing an original vertex, or `None` when
+/// it has no usable influences.
+fn blend_matrix(skinning: &XacSkinning, skin_matrices: &[Mat4], org_vertex: i32) -> Option<Mat4> {
+    let range = skinning
+        .influence_range
+        .get(usize::try_from(org_vertex).ok()?)?;
+    let first = range.first_influence_index.max(0) as usize;
+    let last = (first + range.num_influences.max(0) as usize).min(skinning.influence_data.len());
+    let mut matrix = Mat4::ZERO;
+    let mut total_weight = 0.0;
+    for influence in skinning.influence_data[first.min(last)..last].iter() {
+        if influence.bone_id < 0 || influence.bone_id as usize >= skin_matrices.len() {
+            continue;
+        }
+        matrix += skin_matrices[influence.bone_id as usize] * influence.weight;
+        total_weight += influence.weight;
+    }
+    if total_weight > 0.0 {
+        Some(matrix * (1.0 / total_weight))
+    } else {
+        None
+    }
+}
+
+/// Moves a vertex and its normal. A bone scaled to zero has no inverse, so its normals are
+/// transformed by the matrix itself and collapse to zero instead of NaN.
+fn deform_vertex(
+    matrix: Mat4,
+    position: &XacVec3d,
+    normal: Option<&XacVec3d>,
+) -> (XacVec3d, XacVec3d) {
+    let linear = Mat3::from_mat4(matrix);
+    let normal_matrix = if linear.determinant().abs() > f32::EPSILON {
+        linear.inverse().transpose()
+    } else {
+        linear
+    };
+    let normal = normal
+        .map(|normal| (normal_matrix * Vec3::from(normal)).normalize_or_zero())
+        .unwrap_or(Vec3::ZERO);
+    (
+        XacVec3d::from(matrix.transform_point3(Vec3::from(position))),
+        XacVec3d::from(normal),
+    )
+}
+
+/// Linear blend skinning of every mesh for the given posed node world matrices.
+///
+/// Skinned vertices are moved by their bones' posed world matrix times the inverse of the
+/// bone's bind pose world matrix. Meshes without skinning follow their node.
+pub(crate) fn skin_actor(xac: &Xac, posed_world: &[Mat4]) -> Vec<XacDeformedMesh> {
+    let bind_world = xac.node_world_matrices();
+    let skin_matrices: Vec<Mat4> = posed_world
+        .iter()
+        .zip(bind_world.iter())
+        .map(|(posed, bind)| *posed * bind.inverse())
+        .collect();
+    xac.mesh
+        .iter()
+        .map(|mesh| {
+            let skinning = xac.mesh_skinning(mesh);
+            let fallback = match skinning {
+                Some(_) => node_matrix(&skin_matrices, mesh.node_id),
+                None => node_matrix(posed_world, mesh.node_id),
+            };
+            XacDeformedMesh {
+                node_id: mesh.node_id,
+                sub_mesh: mesh
+                    .sub_mesh
+                    .iter()
+                    .map(|sub| {
+                        let (position, normal) = sub
+                            .sub_position
+                            .iter()
+                            .enumerate()
+                            .map(|(vertex, position)| {
+                                let matrix = skinning
+                                    .zip(sub.sub_influence_range_indices.get(vertex))
+                                    .and_then(|(skinning, &org_vertex)| {
+                                        blend_matrix(skinning, &skin_matrices, org_vertex)
+                                    })
+                                    .unwrap_or(fallback);
+                                deform_vertex(matrix, position, sub.sub_normal.get(vertex))
+                            })
+                            .unzip();
+                        XacDeformedSubMesh { position, normal }
+                    })
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+/// Poses the actor with `xsm` at `time`, matching bones by exact name, and skins it.
+pub(crate) fn deform_actor(xac: &Xac, xsm: &Xsm, time: f32) -> Vec<XacDeformedMesh> {
+    let binding = XsmBinding::new(xac, xsm, &XsmBindOptions::default());
+    skin_actor(xac, &binding.world_matrices(xac, xsm, time))
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::math::Quat;
+
+    use super::*;
+    use crate::xac::xac_fixtures::{actor, mesh, node, quad, vec3d};
+    use crate::xac::xac_structs::{XacInfluenceData, XacInfluenceRange};
+
+    /// Two root bones and a quad on the first one. Corners 0 and 3 follow the second bone,
+    /// corner 1 the first one and corner 2 both of them equally.
+    fn two_bone_actor() -> Xac {
+        let mut xac = actor(vec![
+            node("A", -1, Vec3::ZERO, Quat::IDENTITY),
+            node("B", -1, Vec3::new(0.0, 2.0, 0.0), Quat::IDENTITY),
+        ]);
+        let mut sub = quad(false);
+        sub.sub_normal = vec![vec3d(0.0, 0.0, 1.0); 4];
+        xac.mesh.push(mesh(0, sub));
+        let influence = |bone_id: i16, weight: f32| XacInfluenceData { weight, bone_id };
+        let range = |first_influence_index: i32, num_influences: i32| XacInfluenceRange {
+            first_influence_index,
+            num_influences,
+        };
+        xac.skinning.push(XacSkinning {
+            node_id: 0,
+            num_local_bones: 2,
+            num_influences: 5,
+            for_collision_mesh: 0,
+            influence_data: vec![
+                influence(1, 1.0),
+                influence(0, 1.0),
+                influence(0, 0.5),
+                influence(1, 0.5),
+                influence(1, 1.0),
+            ],
+            influence_range: vec![range(0, 1), range(1, 1), range(2, 2), range(4, 1)],
+        });
+        xac
+    }
+
+    fn positions(meshes: &[XacDeformedMesh]) -> Vec<Vec3> {
+        meshes[0].sub_mesh[0]
+            .position
+            .iter()
+            .map(Vec3::from)
+            .collect()
+    }
+
+    #[test]
+    fn bind_pose_leaves_the_mesh_unchanged() {
+        let xac = two_bone_actor();
+        let meshes = skin_actor(&xac, &xac.node_world_matrices());
+        let original = &xac.mesh[0].sub_mesh[0];
+        for (deformed, position) in positions(&meshes).iter().zip(original.sub_position.iter()) {
+            assert!(deformed.abs_diff_eq(Vec3::from(position), 1e-6));
+        }
+        for normal in meshes[0].sub_mesh[0].normal.iter() {
+            assert!(Vec3::from(normal).abs_diff_eq(Vec3::Z, 1e-6));
+        }
+    }
+
+    #[test]
+    fn vertices_follow_their_weighted_bones() {
+        let xac = two_bone_actor();
+        let mut posed = xac.node_world_matrices();
+        posed[1] = Mat4::from_translation(Vec3::new(1.0, 0.0, 0.0)) * posed[1];
+        let deformed = positions(&skin_actor(&xac, &posed));
+        let expected = [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.5, 1.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+        ];
+        for (deformed, expected) in deformed.iter().zip(expected.iter()) {
+            assert!(deformed.abs_diff_eq(*expected, 1e-6));
+        }
+    }
+
+    #[test]
+    fn zero_scale_bone_keeps_normals_finite() {
+        let xac = two_bone_actor();
+        let mut posed = xac.node_world_matrices();
+        posed[1] = Mat4::from_scale(Vec3::ZERO);
+        let meshes = skin_actor(&xac, &posed);
+        for normal in meshes[0].sub_mesh[0].normal.iter() {
+            assert!(Vec3::from(normal).is_finite());
+        }
+    }
+}
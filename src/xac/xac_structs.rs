@@ -2,6 +2,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::xac::xac_enums::{XacHandedness, XacPhonemeType, XacUpAxis};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct XacVec2d {
     pub(crate) x: f32,
@@ -32,10 +34,10 @@ pub(crate) struct XacColor8 {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct XacQuaternion {
-    pub(crate) x: i32,
-    pub(crate) y: i32,
-    pub(crate) z: i32,
-    pub(crate) w: i32,
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) z: f32,
+    pub(crate) w: f32,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -54,7 +56,7 @@ pub struct Xac {
     pub(crate) material_total: XacMaterialTotal,
     pub(crate) material_definition: XacActorMaterial,
     pub(crate) mesh: Vec<XacActorMesh>,
-    pub(crate) skinning: XacSkinning,
+    pub(crate) skinning: Vec<XacSkinning>,
     pub(crate) shader_material: Vec<XacShaderMaterial>,
     pub(crate) morph_target: XacActorMorphTarget,
     pub(crate) indices: Vec<u32>,
@@ -77,6 +79,13 @@ pub(crate) struct XacChunk {
     pub(crate) version: i32,
 }
 
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct XacCoordinateSystem {
+    pub(crate) up_axis: XacUpAxis,
+    pub(crate) handedness: XacHandedness,
+    pub(crate) unit_scale: f32,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct XacActorMesh {
     pub(crate) node_id: i32,
@@ -116,8 +125,9 @@ pub(crate) struct XacSubMesh {
     pub(crate) sub_normal: Vec<XacVec3d>,
     pub(crate) sub_tangent: Vec<XacVec4d>,
     pub(crate) sub_bi_tangent: Vec<XacVec4d>,
-    pub(crate) sub_uv_set: Vec<XacVec2d>,
+    pub(crate) sub_uv_set: Vec<Vec<XacVec2d>>,
     pub(crate) sub_influence_range_indices: Vec<i32>,
+    pub(crate) sub_indices: Vec<u32>,
     pub(crate) sub_color: Vec<Vec<XacVec3d>>,
     pub(crate) sub_color8: Vec<Vec<XacColor8>>,
 }
@@ -128,6 +138,8 @@ pub(crate) struct XacSkinning {
     pub(crate) num_local_bones: i32,
     pub(crate) num_influences: i32,
     pub(crate) for_collision_mesh: u8,
+    pub(crate) influence_data: Vec<XacInfluenceData>,
+    pub(crate) influence_range: Vec<XacInfluenceRange>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -255,7 +267,11 @@ pub(crate) struct XacNormalOffset {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub(crate) struct XacTangentOffset {}
+pub(crate) struct XacTangentOffset {
+    pub(crate) x: u8,
+    pub(crate) y: u8,
+    pub(crate) z: u8,
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct XacTransformation {
@@ -310,3 +326,44 @@ pub(crate) struct XacShaderMaterial {
     pub(crate) bool_property: Vec<XacBoolProperties>,
     pub(crate) string_property: Vec<XacStringProperties>,
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct XacOptimizeStats {
+    pub(crate) vertices_before: usize,
+    pub(crate) vertices_after: usize,
+    pub(crate) triangles_before: usize,
+    pub(crate) triangles_after: usize,
+    pub(crate) acmr_before: f32,
+    pub(crate) acmr_after: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct XacDeformedMesh {
+    pub(crate) node_id: i32,
+    pub(crate) sub_mesh: Vec<XacDeformedSubMesh>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct XacDeformedSubMesh {
+    pub(crate) position: Vec<XacVec3d>,
+    pub(crate) normal: Vec<XacVec3d>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub(crate) struct XacTimedPhoneme {
+    pub(crate) phoneme: XacPhonemeType,
+    pub(crate) start: f32,
+    pub(crate) end: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct XacMorphCurve {
+    pub(crate) name: String,
+    pub(crate) keys: Vec<XacMorphCurveKey>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub(crate) struct XacMorphCurveKey {
+    pub(crate) time: f32,
+    pub(crate) weight: f32,
+}
@@ -0,0 +1,143 @@
+#![allow(dead_code)]
+
+use bevy_mikktspace::Geometry;
+
+use crate::xac::xac_structs::{XacSubMesh, XacVec2d, XacVec4d};
+
+struct XacTangentGeometry<'a> {
+    sub: &'a XacSubMesh,
+    uv_set: &'a [XacVec2d],
+    tangents: Vec<XacVec4d>,
+}
+
+impl XacTangentGeometry<'_> {
+    fn vertex(&self, face: usize, vert: usize) -> usize {
+        self.sub.sub_indices[face * 3 + vert] as usize
+    }
+}
+
+impl Geometry for XacTangentGeometry<'_> {
+    fn num_faces(&self) -> usize {
+        self.sub.sub_indices.len() / 3
+    }
+
+    fn num_vertices_of_face(&self, _face: usize) -> usize {
+        3
+    }
+
+    fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+        let position = &self.sub.sub_position[self.vertex(face, vert)];
+        [position.x, position.y, position.z]
+    }
+
+    fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        let normal = &self.sub.sub_normal[self.vertex(face, vert)];
+        [normal.x, normal.y, normal.z]
+    }
+
+    fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        let uv = &self.uv_set[self.vertex(face, vert)];
+        [uv.x, uv.y]
+    }
+
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+        let vertex = self.vertex(face, vert);
+        self.tangents[vertex] = XacVec4d {
+            x: tangent[0],
+            y: tangent[1],
+            z: tangent[2],
+            w: tangent[3],
+        };
+    }
+}
+
+/// Generates MikkTSpace tangents for a submesh, the bitangent sign is stored in `w`.
+/// Tangents follow the first UV set, the one normal maps are sampled with.
+/// Returns false when positions, normals, uvs or indices are missing.
+pub(crate) fn generate_tangents(sub: &mut XacSubMesh) -> bool {
+    let vertex_count = sub.num_vertices as usize;
+    if sub.sub_indices.is_empty()
+        || sub.sub_position.len() != vertex_count
+        || sub.sub_normal.len() != vertex_count
+        || sub
+            .sub_indices
+            .iter()
+            .any(|&index| index as usize >= vertex_count)
+    {
+        return false;
+    }
+    let Some(uv_set) = sub
+        .sub_uv_set
+        .first()
+        .filter(|uv_set| uv_set.len() == vertex_count)
+    else {
+        return false;
+    };
+    let mut geometry = XacTangentGeometry {
+        sub,
+        uv_set,
+        tangents: vec![
+            XacVec4d {
+                x: 1.0,
+                y: 0.0,
+                z: 0.0,
+                w: 1.0,
+            };
+            vertex_count
+        ],
+    };
+    if !bevy_mikktspace::generate_tangents(&mut geometry) {
+        return false;
+    }
+    sub.sub_tangent = geometry.tangents;
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xac::xac_fixtures::{quad, vec3d};
+
+    fn lit_quad() -> XacSubMesh {
+        let mut sub = quad(false);
+        sub.sub_normal = (0..4).map(|_| vec3d(0.0, 0.0, 1.0)).collect();
+        sub
+    }
+
+    #[test]
+    fn tangents_follow_the_first_uv_set() {
+        let mut sub = lit_quad();
+        let lightmap = sub.sub_uv_set[0]
+            .iter()
+            .map(|uv| XacVec2d { x: uv.y, y: uv.x })
+            .collect();
+        sub.sub_uv_set.push(lightmap);
+        assert!(generate_tangents(&mut sub));
+        for tangent in sub.sub_tangent.iter() {
+            assert!((tangent.x - 1.0).abs() < 1e-5);
+            assert!(tangent.y.abs() < 1e-5 && tangent.z.abs() < 1e-5);
+            assert_eq!(tangent.w, 1.0);
+        }
+    }
+
+    #[test]
+    fn mirrored_uvs_flip_the_bitangent_sign() {
+        let mut sub = lit_quad();
+        for uv in sub.sub_uv_set[0].iter_mut() {
+            uv.x = 1.0 - uv.x;
+        }
+        assert!(generate_tangents(&mut sub));
+        for tangent in sub.sub_tangent.iter() {
+            assert!((tangent.x + 1.0).abs() < 1e-5);
+            assert_eq!(tangent.w, -1.0);
+        }
+    }
+
+    #[test]
+    fn needs_uvs() {
+        let mut sub = lit_quad();
+        sub.sub_uv_set.clear();
+        assert!(!generate_tangents(&mut sub));
+        assert!(sub.sub_tangent.is_empty());
+    }
+}
@@ -0,0 +1,146 @@
+#![allow(dead_code)]
+
+use bevy::math::{Mat4, Quat, Vec3};
+
+use crate::xac::xac_enums::XacMultiplyOrder;
+use crate::xac::xac_structs::{Xac, XacActorNode, XacHeader};
+
+impl XacHeader {
+    pub(crate) fn multiply_order(&self) -> XacMultiplyOrder {
+        match self.multiply_order {
+            1 => XacMultiplyOrder::RotScaleTrans,
+            _ => XacMultiplyOrder::ScaleRotTrans,
+        }
+    }
+}
+
+/// Builds a local matrix from its components. The scale is applied along the axes
+/// of `scale_rotation`, then combined with the rotation in the declared order.
+pub(crate) fn compose_transform(
+    order: XacMultiplyOrder,
+    position: Vec3,
+    rotation: Quat,
+    scale: Vec3,
+    scale_rotation: Quat,
+) -> Mat4 {
+    let scale = Mat4::from_quat(scale_rotation)
+        * Mat4::from_scale(scale)
+        * Mat4::from_quat(scale_rotation.inverse());
+    let rotation = Mat4::from_quat(rotation);
+    let translation = Mat4::from_translation(position);
+    match order {
+        XacMultiplyOrder::ScaleRotTrans => translation * rotation * scale,
+        XacMultiplyOrder::RotScaleTrans => translation * scale * rotation,
+    }
+}
+
+impl XacActorNode {
+    pub(crate) fn local_matrix(&self, order: XacMultiplyOrder) -> Mat4 {
+        compose_transform(
+            order,
+            Vec3::from(&self.position),
+            Quat::from(&self.rotation),
+            Vec3::from(&self.scale),
+            Quat::from(&self.scale_rotation),
+        )
+    }
+
+    /// The matrix stored in the node chunk, as written by the exporter.
+    pub(crate) fn stored_matrix(&self) -> Mat4 {
+        Mat4::from(&self.transform)
+    }
+}
+
+impl Xac {
+    pub(crate) fn node_local_matrices(&self) -> Vec<Mat4> {
+        let order = self.header.multiply_order();
+        self.node_hierarchy
+            .node_data
+            .iter()
+            .map(|node| node.local_matrix(order))
+            .collect()
+    }
+
+    /// Concatenates local matrices down the node hierarchy. Nodes with an invalid
+    /// or cyclic parent are treated as roots.
+    pub(crate) fn world_matrices(&self, local: &[Mat4]) -> Vec<Mat4> {
+        let nodes = &self.node_hierarchy.node_data;
+        let mut world: Vec<Option<Mat4>> = vec![None; nodes.len()];
+        for start in 0..nodes.len() {
+            let mut chain = vec![];
+            let mut current = start;
+            while world[current].is_none() && !chain.contains(&current) {
+                chain.push(current);
+                let parent = nodes[current].parent_node_id;
+                if parent < 0 || parent as usize >= nodes.len() {
+                    break;
+                }
+                current = parent as usize;
+            }
+            for &node in chain.iter().rev() {
+                let parent = nodes[node].parent_node_id;
+                let parent_world = if parent >= 0 && (parent as usize) < nodes.len() {
+                    world[parent as usize]
+                } else {
+                    None
+                };
+                world[node] = Some(parent_world.unwrap_or(Mat4::IDENTITY) * local[node]);
+            }
+        }
+        world.into_iter().map(|matrix| matrix.unwrap()).collect()
+    }
+
+    pub(crate) fn node_world_matrices(&self) -> Vec<Mat4> {
+        self.world_matrices(&self.node_local_matrices())
+    }
+
+    pub(crate) fn mesh_world_matrix(&self, mesh_index: usize) -> Mat4 {
+        let node_id = self.mesh[mesh_index].node_id;
+        if node_id < 0 || node_id as usize >= self.node_hierarchy.node_data.len() {
+            return Mat4::IDENTITY;
+        }
+        self.node_world_matrices()[node_id as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    #[test]
+    fn multiply_orders_differ_under_non_uniform_scale() {
+        let compose = |order| {
+            compose_transform(
+                order,
+                Vec3::new(0.0, 0.0, 3.0),
+                Quat::from_rotation_z(FRAC_PI_2),
+                Vec3::new(2.0, 1.0, 1.0),
+                Quat::IDENTITY,
+            )
+        };
+        let scale_first = compose(XacMultiplyOrder::ScaleRotTrans);
+        let rotation_first = compose(XacMultiplyOrder::RotScaleTrans);
+        let point = scale_first.transform_point3(Vec3::X);
+        assert!(point.abs_diff_eq(Vec3::new(0.0, 2.0, 3.0), 1e-6));
+        let point = rotation_first.transform_point3(Vec3::X);
+        assert!(point.abs_diff_eq(Vec3::new(0.0, 1.0, 3.0), 1e-6));
+    }
+
+    #[test]
+    fn multiply_orders_agree_under_uniform_scale() {
+        let compose = |order| {
+            compose_transform(
+                order,
+                Vec3::new(1.0, 2.0, 3.0),
+                Quat::from_rotation_y(0.7),
+                Vec3::splat(2.0),
+                Quat::from_rotation_x(0.3),
+            )
+        };
+        let scale_first = compose(XacMultiplyOrder::ScaleRotTrans);
+        let rotation_first = compose(XacMultiplyOrder::RotScaleTrans);
+        assert!(scale_first.abs_diff_eq(rotation_first, 1e-5));
+    }
+}
@@ -0,0 +1,4 @@
+pub(crate) mod xpm_enums;
+pub(crate) mod xpm_parser;
+pub(crate) mod xpm_sample;
+pub(crate) mod xpm_structs;
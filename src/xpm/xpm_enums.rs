@@ -0,0 +1,7 @@
+#![allow(dead_code)]
+#[allow(clippy::enum_variant_names)]
+pub(crate) enum XpmChunkType {
+    XpmSubMotionId = 100,
+    XpmInfoId = 101,
+    XpmSubMotionsId = 102,
+}
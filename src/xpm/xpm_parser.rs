@@ -0,0 +1,182 @@
+#![allow(dead_code)]
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
+
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt};
+
+use crate::xpm::xpm_enums::XpmChunkType::{XpmInfoId, XpmSubMotionId, XpmSubMotionsId};
+use crate::xpm::xpm_structs::{Xpm, XpmFloatKey, XpmInfo, XpmSubMotion};
+use crate::xsm::xsm_enums::XsmError;
+use crate::xsm::xsm_parser::{read_header, xsm_read_string};
+
+pub fn xpmparse(path: &str) -> Result<Xpm, XsmError> {
+    let xpm_file = File::open(path)?;
+    Xpm::from_reader(BufReader::new(xpm_file))
+}
+
+impl Xpm {
+    pub fn from_reader<R: Read + Seek>(mut reader: R) -> Result<Xpm, XsmError> {
+        let header = read_header(&mut reader)?;
+        if header.magic != "XPM " {
+            return Err(XsmError::InvalidMagic(header.magic));
+        }
+        let mut xpm_new = Xpm {
+            header,
+            info: XpmInfo {
+                fps: 0,
+                exporter_major_version: 0,
+                exporter_minor_version: 0,
+                source_app: "".to_string(),
+                original_filename: "".to_string(),
+                export_date: "".to_string(),
+                motion_name: "".to_string(),
+            },
+            submotions: vec![],
+        };
+        if xpm_new.header.big_endian {
+            read_chunk::<BigEndian, R>(&mut reader, &mut xpm_new)?;
+        } else {
+            read_chunk::<LittleEndian, R>(&mut reader, &mut xpm_new)?;
+        }
+        Ok(xpm_new)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Xpm, XsmError> {
+        Xpm::from_reader(Cursor::new(bytes))
+    }
+}
+
+fn read_chunk<E: ByteOrder, R: Read + Seek>(file: &mut R, xpm: &mut Xpm) -> Result<(), XsmError> {
+    let mut position = file.stream_position()?;
+    let end = file.seek(SeekFrom::End(0))?;
+    file.seek(SeekFrom::Start(position))?;
+    while position < end {
+        let chunk_type = file.read_i32::<E>()?;
+        let length = file.read_i32::<E>()?;
+        let version = file.read_i32::<E>()?;
+        position = file.stream_position()?;
+        if length < 0 || position + length as u64 > end {
+            return Err(XsmError::InvalidChunkLength { chunk_type, length });
+        }
+        let known = [XpmSubMotionId, XpmInfoId, XpmSubMotionsId]
+            .into_iter()
+            .any(|known| known as i32 == chunk_type);
+        if known && version != 1 {
+            return Err(XsmError::UnsupportedChunkVersion {
+                chunk_type,
+                version,
+            });
+        }
+        if chunk_type == XpmInfoId as i32 {
+            read_info::<E, R>(file, xpm)?;
+        }
+        if chunk_type == XpmSubMotionId as i32 {
+            let submotion = read_submotion::<E, R>(file)?;
+            xpm.submotions.push(submotion);
+        }
+        if chunk_type == XpmSubMotionsId as i32 {
+            let num_submotions = file.read_i32::<E>()?;
+            for _ in 0..num_submotions {
+                let submotion = read_submotion::<E, R>(file)?;
+                xpm.submotions.push(submotion);
+            }
+        }
+        position = file.seek(SeekFrom::Start(position + length as u64))?;
+    }
+    Ok(())
+}
+
+fn read_info<E: ByteOrder, R: Read>(file: &mut R, xpm: &mut Xpm) -> Result<(), XsmError> {
+    xpm.info.fps = file.read_i32::<E>()?;
+    xpm.info.exporter_major_version = file.read_u8()?;
+    xpm.info.exporter_minor_version = file.read_u8()?;
+    file.read_u8()?; //Padding
+    file.read_u8()?; //Padding
+    xpm.info.source_app = xsm_read_string::<E, R>(file)?;
+    xpm.info.original_filename = xsm_read_string::<E, R>(file)?;
+    xpm.info.export_date = xsm_read_string::<E, R>(file)?;
+    xpm.info.motion_name = xsm_read_string::<E, R>(file)?;
+    Ok(())
+}
+
+fn read_submotion<E: ByteOrder, R: Read>(file: &mut R) -> Result<XpmSubMotion, XsmError> {
+    let mut submotion = XpmSubMotion {
+        pose_weight: file.read_f32::<E>()?,
+        min_weight: file.read_f32::<E>()?,
+        max_weight: file.read_f32::<E>()?,
+        phoneme_set: file.read_u32::<E>()?,
+        num_keys: file.read_i32::<E>()?,
+        name: "".to_string(),
+        keys: vec![],
+    };
+    submotion.name = xsm_read_string::<E, R>(file)?;
+    for _ in 0..submotion.num_keys {
+        let time = file.read_f32::<E>()?;
+        let value = file.read_u16::<E>()? as f32 / u16::MAX as f32;
+        file.read_u16::<E>()?; // Padding
+        submotion.keys.push(XpmFloatKey { time, value });
+    }
+    Ok(submotion)
+}
+
+#[cfg(test)]
+mod tests {
+    use byteorder::WriteBytesExt;
+
+    use super::*;
+
+    fn file<E: ByteOrder>(big_endian: bool) -> Vec<u8> {
+        let string = |body: &mut Vec<u8>, text: &str| {
+            body.write_i32::<E>(text.len() as i32).unwrap();
+            body.extend_from_slice(text.as_bytes());
+        };
+        let mut info = vec![];
+        info.write_i32::<E>(30).unwrap();
+        info.extend_from_slice(&[2, 1, 0, 0]);
+        for text in ["3ds max", "smile.max", "today", "smile"] {
+            string(&mut info, text);
+        }
+        let mut submotions = vec![];
+        submotions.write_i32::<E>(1).unwrap();
+        for value in [0.0, 0.0, 1.0] {
+            submotions.write_f32::<E>(value).unwrap();
+        }
+        submotions.write_u32::<E>(4).unwrap();
+        submotions.write_i32::<E>(3).unwrap();
+        string(&mut submotions, "Smile");
+        for (time, value) in [(0.0, 0), (0.5, 65535), (1.0, 32768)] {
+            submotions.write_f32::<E>(time).unwrap();
+            submotions.write_u16::<E>(value).unwrap();
+            submotions.write_u16::<E>(0).unwrap();
+        }
+        let mut file = b"XPM ".to_vec();
+        file.extend_from_slice(&[1, 0, big_endian as u8, 0]);
+        for (chunk_type, body) in [(101, info), (102, submotions)] {
+            file.write_i32::<E>(chunk_type).unwrap();
+            file.write_i32::<E>(body.len() as i32).unwrap();
+            file.write_i32::<E>(1).unwrap();
+            file.extend_from_slice(&body);
+        }
+        file
+    }
+
+    #[test]
+    fn decodes_weight_keys() {
+        for bytes in [file::<LittleEndian>(false), file::<BigEndian>(true)] {
+            let xpm = Xpm::from_bytes(&bytes).unwrap();
+            assert_eq!(xpm.info.motion_name, "smile");
+            let submotion = &xpm.submotions[0];
+            assert_eq!(submotion.name, "Smile");
+            let values: Vec<f32> = submotion.keys.iter().map(|key| key.value).collect();
+            assert_eq!(values[..2], [0.0, 1.0]);
+            assert!((values[2] - 0.5).abs() < 1e-4);
+            assert_eq!(submotion.keys[2].time, 1.0);
+        }
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        let result = Xpm::from_bytes(b"XSM \x01\x00\x00\x00");
+        assert!(matches!(result, Err(XsmError::InvalidMagic(_))));
+    }
+}
@@ -0,0 +1,49 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use crate::xpm::xpm_structs::{Xpm, XpmSubMotion};
+use crate::xsm::xsm_sample::key_segment;
+
+impl XpmSubMotion {
+    /// Weight at `time`, linearly interpolated between keys and held outside the key
+    /// range. Submotions without keys stay at their pose weight.
+    pub(crate) fn sample(&self, time: f32) -> f32 {
+        if self.keys.is_empty() {
+            return self.pose_weight;
+        }
+        let times: Vec<f32> = self.keys.iter().map(|key| key.time).collect();
+        let (previous, next, factor) = key_segment(&times, time);
+        let (previous, next) = (self.keys[previous].value, self.keys[next].value);
+        previous + (next - previous) * factor
+    }
+
+    pub(crate) fn duration(&self) -> f32 {
+        self.keys.iter().map(|key| key.time).fold(0.0, f32::max)
+    }
+}
+
+impl Xpm {
+    pub(crate) fn duration(&self) -> f32 {
+        self.submotions
+            .iter()
+            .map(|submotion| submotion.duration())
+            .fold(0.0, f32::max)
+    }
+
+    /// Weight of the morph target `name` at `time`, if the motion animates it.
+    pub(crate) fn sample_weight(&self, name: &str, time: f32) -> Option<f32> {
+        self.submotions
+            .iter()
+            .find(|submotion| submotion.name == name)
+            .map(|submotion| submotion.sample(time))
+    }
+
+    /// Weights of every animated morph target at `time`, keyed by target name.
+    pub(crate) fn sample(&self, time: f32) -> HashMap<String, f32> {
+        self.submotions
+            .iter()
+            .map(|submotion| (submotion.name.clone(), submotion.sample(time)))
+            .collect()
+    }
+}
@@ -0,0 +1,42 @@
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+
+use crate::xsm::xsm_structs::XsmHeader;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Xpm {
+    pub(crate) header: XsmHeader,
+    pub(crate) info: XpmInfo,
+    pub(crate) submotions: Vec<XpmSubMotion>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct XpmInfo {
+    pub(crate) fps: i32,
+    pub(crate) exporter_major_version: u8,
+    pub(crate) exporter_minor_version: u8,
+    pub(crate) source_app: String,
+    pub(crate) original_filename: String,
+    pub(crate) export_date: String,
+    pub(crate) motion_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct XpmSubMotion {
+    pub(crate) pose_weight: f32,
+    pub(crate) min_weight: f32,
+    pub(crate) max_weight: f32,
+    pub(crate) phoneme_set: u32,
+    pub(crate) num_keys: i32,
+    pub(crate) name: String,
+    pub(crate) keys: Vec<XpmFloatKey>,
+}
+
+/// A weight key. Files store the weight as a `u16` over 0..=65535 followed by two bytes of
+/// padding; `value` holds it already scaled to 0..=1.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub(crate) struct XpmFloatKey {
+    pub(crate) time: f32,
+    pub(crate) value: f32,
+}
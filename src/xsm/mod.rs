@@ -1,3 +1,18 @@
+pub(crate) mod xsm_bind;
+pub(crate) mod xsm_blend;
+pub(crate) mod xsm_convert;
 pub(crate) mod xsm_enums;
+#[cfg(test)]
+pub(crate) mod xsm_fixtures;
+pub(crate) mod xsm_math;
+pub(crate) mod xsm_mirror;
 pub(crate) mod xsm_parser;
+pub(crate) mod xsm_reduce;
+pub(crate) mod xsm_resample;
+pub(crate) mod xsm_retarget;
+pub(crate) mod xsm_root_motion;
+pub(crate) mod xsm_sample;
 pub(crate) mod xsm_structs;
+pub(crate) mod xsm_summary;
+pub(crate) mod xsm_validate;
+pub(crate) mod xsm_writer;
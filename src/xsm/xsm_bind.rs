@@ -0,0 +1,158 @@
+#![allow(dead_code)]
+
+use bevy::math::{Mat4, Quat, Vec3};
+
+use crate::xac::xac_enums::XacMultiplyOrder;
+use crate::xac::xac_structs::Xac;
+use crate::xac::xac_transform::compose_transform;
+use crate::xsm::xsm_structs::{Xsm, XsmBindOptions, XsmBinding, XsmBoundTrack, XsmNodePose};
+
+impl XsmBindOptions {
+    /// Maps a motion bone name to the actor bone name it should be compared against.
+    pub(crate) fn with_alias(mut self, motion_name: &str, actor_name: &str) -> Self {
+        self.aliases
+            .insert(motion_name.to_string(), actor_name.to_string());
+        self
+    }
+
+    fn matches(&self, motion_name: &str, actor_name: &str) -> bool {
+        let motion_name = self
+            .aliases
+            .get(motion_name)
+            .map(String::as_str)
+            .unwrap_or(motion_name);
+        if self.case_insensitive {
+            motion_name.eq_ignore_ascii_case(actor_name)
+        } else {
+            motion_name == actor_name
+        }
+    }
+}
+
+impl XsmNodePose {
+    pub(crate) fn local_matrix(&self, order: XacMultiplyOrder) -> Mat4 {
+        compose_transform(
+            order,
+            Vec3::from(&self.position),
+            Quat::from(self.rotation),
+            Vec3::from(&self.scale),
+            Quat::from(self.scale_rotation),
+        )
+    }
+}
+
+impl XsmBinding {
+    /// Matches every submotion of `xsm` to a node of `xac` by name. A node is driven by
+    /// the first submotion that matches it; later ones are reported as unmatched.
+    pub(crate) fn new(xac: &Xac, xsm: &Xsm, options: &XsmBindOptions) -> XsmBinding {
+        let nodes = &xac.node_hierarchy.node_data;
+        let mut bound = vec![false; nodes.len()];
+        let mut tracks = vec![];
+        let mut unmatched_motion_bones = vec![];
+        for (submotion_index, submotion) in xsm.bone_animation.skeletal_submotion.iter().enumerate()
+        {
+            let node_index = (0..nodes.len()).find(|&index| {
+                !bound[index] && options.matches(&submotion.node_name, &nodes[index].name)
+            });
+            match node_index {
+                Some(node_index) => {
+                    bound[node_index] = true;
+                    tracks.push(XsmBoundTrack {
+                        submotion_index,
+                        node_index,
+                    });
+                }
+                None => unmatched_motion_bones.push(submotion.node_name.clone()),
+            }
+        }
+        XsmBinding {
+            tracks,
+            unmatched_motion_bones,
+            unmatched_actor_bones: nodes
+                .iter()
+                .zip(bound)
+                .filter(|(_, bound)| !bound)
+                .map(|(node, _)| node.name.clone())
+                .collect(),
+        }
+    }
+
+    /// Local matrices of every actor node at `time`. Nodes without a track keep their
+    /// bind pose.
+    pub(crate) fn local_matrices(&self, xac: &Xac, xsm: &Xsm, time: f32) -> Vec<Mat4> {
+        let order = xac.header.multiply_order();
+        let mut local = xac.node_local_matrices();
+        for track in self.tracks.iter() {
+            let pose = xsm.bone_animation.skeletal_submotion[track.submotion_index].sample(time);
+            local[track.node_index] = pose.local_matrix(order);
+        }
+        local
+    }
+
+    pub(crate) fn world_matrices(&self, xac: &Xac, xsm: &Xsm, time: f32) -> Vec<Mat4> {
+        xac.world_matrices(&self.local_matrices(xac, xsm, time))
+    }
+}
+
+impl Xsm {
+    pub(crate) fn bind(&self, xac: &Xac, options: &XsmBindOptions) -> XsmBinding {
+        XsmBinding::new(xac, self, options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xac::xac_fixtures::{actor, node};
+    use crate::xsm::xsm_fixtures::motion;
+
+    fn skeleton() -> Xac {
+        actor(vec![
+            node("Bip01", -1, Vec3::ZERO, Quat::IDENTITY),
+            node("Bip01 Spine", 0, Vec3::Y, Quat::IDENTITY),
+            node("Bip01 Head", 1, Vec3::Y, Quat::IDENTITY),
+        ])
+    }
+
+    fn bound_nodes(binding: &XsmBinding) -> Vec<(usize, usize)> {
+        binding
+            .tracks
+            .iter()
+            .map(|track| (track.submotion_index, track.node_index))
+            .collect()
+    }
+
+    #[test]
+    fn exact_names_match_and_the_rest_is_reported() {
+        let xsm = motion(&["Bip01", "BIP01 SPINE", "Tail"], 2);
+        let binding = xsm.bind(&skeleton(), &XsmBindOptions::default());
+        assert_eq!(bound_nodes(&binding), vec![(0, 0)]);
+        assert_eq!(binding.unmatched_motion_bones, vec!["BIP01 SPINE", "Tail"]);
+        assert_eq!(
+            binding.unmatched_actor_bones,
+            vec!["Bip01 Spine", "Bip01 Head"]
+        );
+    }
+
+    #[test]
+    fn case_insensitive_names_and_aliases_match() {
+        let xsm = motion(&["BIP01", "bip01 spine", "Head", "Tail"], 2);
+        let options = XsmBindOptions {
+            case_insensitive: true,
+            ..Default::default()
+        }
+        .with_alias("Head", "Bip01 Head");
+        let binding = xsm.bind(&skeleton(), &options);
+        assert_eq!(bound_nodes(&binding), vec![(0, 0), (1, 1), (2, 2)]);
+        assert_eq!(binding.unmatched_motion_bones, vec!["Tail"]);
+        assert!(binding.unmatched_actor_bones.is_empty());
+    }
+
+    #[test]
+    fn a_node_is_driven_by_its_first_track_only() {
+        let xsm = motion(&["Bip01", "Bip01"], 2);
+        let binding = xsm.bind(&skeleton(), &XsmBindOptions::default());
+        assert_eq!(bound_nodes(&binding), vec![(0, 0)]);
+        assert_eq!(binding.unmatched_motion_bones, vec!["Bip01"]);
+    }
+}
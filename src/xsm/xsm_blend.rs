@@ -0,0 +1,248 @@
+#![allow(dead_code)]
+
+use bevy::math::{Quat, Vec3};
+
+use crate::xac::xac_structs::Xac;
+use crate::xsm::xsm_structs::{
+    Xsm, XsmBoneMask, XsmNodePose, XsmPose, XsmQuaternion, XsmSubMotion, XsmVec3d,
+};
+
+impl XsmBoneMask {
+    pub(crate) fn from_bones(node_names: &[&str]) -> XsmBoneMask {
+        XsmBoneMask {
+            weights: node_names
+                .iter()
+                .map(|node_name| (node_name.to_string(), 1.0))
+                .collect(),
+        }
+    }
+
+    /// Selects `root_name` and every node below it in the actor's hierarchy, for example
+    /// the spine to mask the upper body.
+    pub(crate) fn from_subtree(xac: &Xac, root_name: &str) -> XsmBoneMask {
+        let nodes = &xac.node_hierarchy.node_data;
+        let in_subtree = |start: usize| {
+            let mut current = start;
+            for _ in 0..nodes.len() {
+                if nodes[current].name == root_name {
+                    return true;
+                }
+                let parent = nodes[current].parent_node_id;
+                if parent < 0 || parent as usize >= nodes.len() {
+                    return false;
+                }
+                current = parent as usize;
+            }
+            false
+        };
+        XsmBoneMask {
+            weights: (0..nodes.len())
+                .filter(|&index| in_subtree(index))
+                .map(|index| (nodes[index].name.clone(), 1.0))
+                .collect(),
+        }
+    }
+
+    /// Weight of a bone, zero for bones outside the mask.
+    pub(crate) fn weight(&self, node_name: &str) -> f32 {
+        self.weights.get(node_name).copied().unwrap_or(0.0)
+    }
+}
+
+fn mask_weight(mask: Option<&XsmBoneMask>, node_name: &str, weight: f32) -> f32 {
+    mask.map_or(1.0, |mask| mask.weight(node_name)) * weight
+}
+
+impl XsmNodePose {
+    fn blend(&self, other: &XsmNodePose, weight: f32) -> XsmNodePose {
+        XsmNodePose {
+            node_name: self.node_name.clone(),
+            position: XsmVec3d::from(
+                Vec3::from(&self.position).lerp(Vec3::from(&other.position), weight),
+            ),
+            rotation: XsmQuaternion::from(
+                Quat::from(self.rotation)
+                    .slerp(Quat::from(other.rotation), weight)
+                    .normalize(),
+            ),
+            scale: XsmVec3d::from(Vec3::from(&self.scale).lerp(Vec3::from(&other.scale), weight)),
+            scale_rotation: XsmQuaternion::from(
+                Quat::from(self.scale_rotation)
+                    .slerp(Quat::from(other.scale_rotation), weight)
+                    .normalize(),
+            ),
+        }
+    }
+}
+
+impl XsmSubMotion {
+    /// Difference between the pose at `time` and the bind pose, as an offset, a rotation
+    /// applied after the base rotation and a scale factor.
+    pub(crate) fn additive_delta(&self, time: f32) -> (Vec3, Quat, Vec3) {
+        let pose = self.sample(time);
+        let bind_scale = Vec3::from(&self.bind_pose_scale_pos);
+        let scale = Vec3::from(&pose.scale);
+        let scale_factor =
+            Vec3::select(bind_scale.cmpeq(Vec3::ZERO), Vec3::ONE, scale / bind_scale);
+        (
+            Vec3::from(&pose.position) - Vec3::from(&self.bind_pose_pos),
+            (Quat::from(self.bind_pose_rotation()).inverse() * Quat::from(pose.rotation))
+                .normalize(),
+            scale_factor,
+        )
+    }
+}
+
+impl XsmPose {
+    /// Cross-fades towards `other` by `weight`, scaled per bone by `mask`. Bones that only
+    /// one of the poses has are kept as they are.
+    pub(crate) fn blend(
+        &self,
+        other: &XsmPose,
+        weight: f32,
+        mask: Option<&XsmBoneMask>,
+    ) -> XsmPose {
+        let mut nodes: Vec<XsmNodePose> = self
+            .nodes
+            .iter()
+            .map(|node| match other.node(&node.node_name) {
+                Some(target) => node.blend(
+                    target,
+                    mask_weight(mask, &node.node_name, weight).clamp(0.0, 1.0),
+                ),
+                None => node.clone(),
+            })
+            .collect();
+        nodes.extend(
+            other
+                .nodes
+                .iter()
+                .filter(|node| self.node(&node.node_name).is_none())
+                .cloned(),
+        );
+        XsmPose { nodes }
+    }
+
+    /// Adds the difference between `additive` at `time` and its bind pose on top of this
+    /// pose, scaled by `weight` and per bone by `mask`.
+    pub(crate) fn add_layer(
+        &mut self,
+        additive: &Xsm,
+        time: f32,
+        weight: f32,
+        mask: Option<&XsmBoneMask>,
+    ) {
+        for node in self.nodes.iter_mut() {
+            let Some(submotion) = additive
+                .bone_animation
+                .skeletal_submotion
+                .iter()
+                .find(|submotion| submotion.node_name == node.node_name)
+            else {
+                continue;
+            };
+            let weight = mask_weight(mask, &node.node_name, weight);
+            let (offset, rotation, scale) = submotion.additive_delta(time);
+            node.position = XsmVec3d::from(Vec3::from(&node.position) + offset * weight);
+            node.rotation = XsmQuaternion::from(
+                (Quat::from(node.rotation) * Quat::IDENTITY.slerp(rotation, weight)).normalize(),
+            );
+            node.scale = XsmVec3d::from(Vec3::from(&node.scale) * Vec3::ONE.lerp(scale, weight));
+        }
+    }
+}
+
+impl Xsm {
+    /// Samples this motion at `time` and `other` at `other_time` and cross-fades between
+    /// them by `weight`.
+    pub(crate) fn blend(
+        &self,
+        time: f32,
+        other: &Xsm,
+        other_time: f32,
+        weight: f32,
+        mask: Option<&XsmBoneMask>,
+    ) -> XsmPose {
+        self.sample(time)
+            .blend(&other.sample(other_time), weight, mask)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xsm::xsm_fixtures::motion;
+
+    const NAMES: [&str; 2] = ["Bip01", "Bip01 Head"];
+
+    /// The fixture motion moved 5 units along z and turned a quarter around x.
+    fn shifted() -> Xsm {
+        let mut xsm = motion(&NAMES, 10);
+        for submotion in xsm.bone_animation.skeletal_submotion.iter_mut() {
+            for key in submotion.pos_key.iter_mut() {
+                key.pos.z += 5.0;
+            }
+            for key in submotion.rot_key.iter_mut() {
+                let rotation = Quat::from_rotation_x(1.5) * Quat::from(key.rotation());
+                key.set_rotation(XsmQuaternion::from(rotation));
+            }
+        }
+        xsm
+    }
+
+    fn assert_same(a: &XsmNodePose, b: &XsmNodePose) {
+        assert_eq!(a.node_name, b.node_name);
+        assert!(Vec3::from(&a.position).abs_diff_eq(Vec3::from(&b.position), 1e-5));
+        assert!(Quat::from(a.rotation).angle_between(Quat::from(b.rotation)) < 1e-3);
+        assert!(Vec3::from(&a.scale).abs_diff_eq(Vec3::from(&b.scale), 1e-5));
+    }
+
+    #[test]
+    fn cross_fade_ends_on_either_motion() {
+        let (from, to) = (motion(&NAMES, 10), shifted());
+        let time = 0.15;
+        for (weight, expected) in [(0.0, from.sample(time)), (1.0, to.sample(time))] {
+            let blended = from.blend(time, &to, time, weight, None);
+            for (node, expected) in blended.nodes.iter().zip(expected.nodes.iter()) {
+                assert_same(node, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn masked_out_bones_keep_the_base_pose() {
+        let (from, to) = (motion(&NAMES, 10), shifted());
+        let mask = XsmBoneMask::from_bones(&["Bip01"]);
+        let blended = from.blend(0.2, &to, 0.2, 1.0, Some(&mask));
+        assert_same(
+            blended.node("Bip01").unwrap(),
+            to.sample(0.2).node("Bip01").unwrap(),
+        );
+        let head = from.sample(0.2);
+        assert_same(
+            blended.node("Bip01 Head").unwrap(),
+            head.node("Bip01 Head").unwrap(),
+        );
+    }
+
+    #[test]
+    fn additive_layer_holding_the_bind_pose_changes_nothing() {
+        let base = shifted();
+        let mut additive = motion(&NAMES, 10);
+        for submotion in additive.bone_animation.skeletal_submotion.iter_mut() {
+            let (position, rotation) = (submotion.bind_pose_pos, submotion.bind_pose_rotation());
+            for key in submotion.pos_key.iter_mut() {
+                key.pos = position;
+            }
+            for key in submotion.rot_key.iter_mut() {
+                key.set_rotation(rotation);
+            }
+        }
+        let expected = base.sample(0.1);
+        let mut pose = base.sample(0.1);
+        pose.add_layer(&additive, 0.1, 1.0, None);
+        for (node, expected) in pose.nodes.iter().zip(expected.nodes.iter()) {
+            assert_same(node, expected);
+        }
+    }
+}
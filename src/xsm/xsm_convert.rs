@@ -0,0 +1,50 @@
+#![allow(dead_code)]
+
+use bevy::math::{Quat, Vec3};
+
+use crate::xac::xac_convert::XacConversion;
+use crate::xac::xac_structs::XacCoordinateSystem;
+use crate::xsm::xsm_structs::{Xsm, XsmQuaternion, XsmVec3d};
+
+fn position(conversion: &XacConversion, value: &mut XsmVec3d) {
+    *value = XsmVec3d::from(conversion.position(Vec3::from(&*value)));
+}
+
+fn scale(conversion: &XacConversion, value: &mut XsmVec3d) {
+    *value = XsmVec3d::from(conversion.scale(Vec3::from(&*value)));
+}
+
+fn rotation(conversion: &XacConversion, value: XsmQuaternion) -> XsmQuaternion {
+    XsmQuaternion::from(conversion.rotation(Quat::from(value)))
+}
+
+/// Converts every pose, bind pose and animation key of a motion between coordinate
+/// systems, matching `convert_actor` for the actor it plays on.
+pub(crate) fn convert_motion(xsm: &mut Xsm, from: XacCoordinateSystem, to: XacCoordinateSystem) {
+    let conversion = XacConversion::new(from, to);
+    for submotion in xsm.bone_animation.skeletal_submotion.iter_mut() {
+        submotion.set_pose_rotation(rotation(&conversion, submotion.pose_rotation()));
+        submotion.set_bind_pose_rotation(rotation(&conversion, submotion.bind_pose_rotation()));
+        submotion.set_pose_scale_rotation(rotation(&conversion, submotion.pose_scale_rotation()));
+        submotion.set_bind_pose_scale_rotation(rotation(
+            &conversion,
+            submotion.bind_pose_scale_rotation(),
+        ));
+        position(&conversion, &mut submotion.pose_pos);
+        scale(&conversion, &mut submotion.pose_scale);
+        position(&conversion, &mut submotion.bind_pose_pos);
+        scale(&conversion, &mut submotion.bind_pose_scale_pos);
+        for key in submotion.pos_key.iter_mut() {
+            position(&conversion, &mut key.pos);
+        }
+        for key in submotion.rot_key.iter_mut() {
+            key.set_rotation(rotation(&conversion, key.rotation()));
+        }
+        for key in submotion.scale_key.iter_mut() {
+            scale(&conversion, &mut key.scale);
+        }
+        for key in submotion.scale_rot_key.iter_mut() {
+            key.set_rotation(rotation(&conversion, key.rotation()));
+        }
+    }
+}
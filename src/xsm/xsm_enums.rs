@@ -1,5 +1,83 @@
 #![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+
+#[allow(clippy::enum_variant_names)]
 pub(crate) enum XsmChunkType {
+    XsmSubMotionId = 200,
     XsmMetadataId = 201,
     XsmBoneAnimationId = 202,
 }
+
+#[derive(Debug)]
+pub enum XsmError {
+    Io(std::io::Error),
+    InvalidMagic(String),
+    InvalidChunkLength { chunk_type: i32, length: i32 },
+    UnsupportedChunkVersion { chunk_type: i32, version: i32 },
+}
+
+impl std::fmt::Display for XsmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XsmError::Io(error) => write!(f, "Cannot read motion data: {}", error),
+            XsmError::InvalidMagic(magic) => write!(f, "Wrong file header: {:?}", magic),
+            XsmError::InvalidChunkLength { chunk_type, length } => write!(
+                f,
+                "Chunk {} has an invalid length of {} bytes",
+                chunk_type, length
+            ),
+            XsmError::UnsupportedChunkVersion {
+                chunk_type,
+                version,
+            } => write!(
+                f,
+                "Chunk {} version {} is not supported",
+                chunk_type, version
+            ),
+        }
+    }
+}
+
+impl std::error::Error for XsmError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            XsmError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for XsmError {
+    fn from(error: std::io::Error) -> Self {
+        XsmError::Io(error)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum XsmWrapMode {
+    Clamp,
+    Loop,
+}
+
+/// Plane a motion is mirrored across, named by the two axes it contains.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) enum XsmMirrorPlane {
+    YZ,
+    XZ,
+    XY,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) enum XsmChannel {
+    Position,
+    Rotation,
+    Scale,
+    ScaleRotation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub(crate) enum XsmKeyProblem {
+    NotFinite,
+    TimeGoesBackwards,
+}
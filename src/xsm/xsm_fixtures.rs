@@ -0,0 +1,165 @@
+//! Synthetic motions and files shared by the xsm tests.
+
+use byteorder::{ByteOrder, WriteBytesExt};
+
+use crate::xsm::xsm_structs::{
+    Xsm, XsmBoneAnimation, XsmHeader, XsmMetadata, XsmPosKey, XsmQuaternion16, XsmRotKey,
+    XsmRotation, XsmSubMotion, XsmVec3d,
+};
+
+pub(crate) fn vec3d(x: f32, y: f32, z: f32) -> XsmVec3d {
+    XsmVec3d { x, y, z }
+}
+
+/// A track moving linearly along x and turning around y, one key every 1/30 s.
+pub(crate) fn submotion(node_name: &str, num_keys: usize) -> XsmSubMotion {
+    let identity = XsmRotation::Compressed(XsmQuaternion16 {
+        x: 0,
+        y: 0,
+        z: 0,
+        w: 32767,
+    });
+    let mut submotion = XsmSubMotion {
+        pose_rot: identity,
+        bind_pose_rot: identity,
+        pose_scale_rot: identity,
+        bind_pose_scale_rot: identity,
+        pose_pos: vec3d(0.0, 1.0, 0.0),
+        pose_scale: vec3d(1.0, 1.0, 1.0),
+        bind_pose_pos: vec3d(0.0, 1.0, 0.0),
+        bind_pose_scale_pos: vec3d(1.0, 1.0, 1.0),
+        num_pos_keys: num_keys as i32,
+        num_rot_keys: num_keys as i32,
+        num_scale_keys: 0,
+        num_scale_rot_keys: 0,
+        max_error: 0.0,
+        node_name: node_name.to_string(),
+        pos_key: vec![],
+        rot_key: vec![],
+        scale_key: vec![],
+        scale_rot_key: vec![],
+    };
+    for key in 0..num_keys {
+        let time = key as f32 / 30.0;
+        submotion.pos_key.push(XsmPosKey {
+            pos: vec3d(time * 2.0, 1.0, 0.0),
+            time,
+        });
+        submotion.rot_key.push(XsmRotKey {
+            rot: identity.with_quaternion(bevy::math::Quat::from_rotation_y(time).into()),
+            time,
+        });
+    }
+    submotion
+}
+
+pub(crate) fn motion(node_names: &[&str], num_keys: usize) -> Xsm {
+    Xsm {
+        header: XsmHeader {
+            magic: "XSM ".to_string(),
+            major_version: 1,
+            minor_version: 0,
+            big_endian: false,
+        },
+        metadata: XsmMetadata {
+            unused: 1.0,
+            max_acceptable_error: 0.001,
+            fps: 30,
+            exporter_major_version: 1,
+            exporter_minor_version: 0,
+            source_app: "3ds max".to_string(),
+            original_filename: "run.max".to_string(),
+            export_date: "today".to_string(),
+            motion_name: "run".to_string(),
+        },
+        bone_animation: XsmBoneAnimation {
+            num_submotion: node_names.len() as i32,
+            skeletal_submotion: node_names
+                .iter()
+                .map(|node_name| submotion(node_name, num_keys))
+                .collect(),
+        },
+        chunks: vec![],
+        events: vec![],
+    }
+}
+
+fn string<E: ByteOrder>(body: &mut Vec<u8>, text: &str) {
+    body.write_i32::<E>(text.len() as i32).unwrap();
+    body.extend_from_slice(text.as_bytes());
+}
+
+fn chunk<E: ByteOrder>(file: &mut Vec<u8>, chunk_type: i32, version: i32, body: &[u8]) {
+    file.write_i32::<E>(chunk_type).unwrap();
+    file.write_i32::<E>(body.len() as i32).unwrap();
+    file.write_i32::<E>(version).unwrap();
+    file.extend_from_slice(body);
+}
+
+/// Writes one submotion with two position keys and one rotation key.
+fn submotion_body<E: ByteOrder>(body: &mut Vec<u8>, node_name: &str, compressed: bool) {
+    let rotation = |body: &mut Vec<u8>, x: f32, w: f32| {
+        if compressed {
+            for value in [x, 0.0, 0.0, w] {
+                body.write_i16::<E>((value * 32767.0) as i16).unwrap();
+            }
+        } else {
+            for value in [x, 0.0, 0.0, w] {
+                body.write_f32::<E>(value).unwrap();
+            }
+        }
+    };
+    for _ in 0..4 {
+        rotation(body, 0.0, 1.0);
+    }
+    for value in [0.0, 1.0, 0.0, 1.0, 1.0, 1.0, 0.0, 1.0, 0.0, 1.0, 1.0, 1.0] {
+        body.write_f32::<E>(value).unwrap();
+    }
+    for count in [2, 1, 0, 0] {
+        body.write_i32::<E>(count).unwrap();
+    }
+    if compressed {
+        body.write_f32::<E>(0.01).unwrap();
+    }
+    string::<E>(body, node_name);
+    for (x, time) in [(0.0, 0.0), (3.0, 1.5)] {
+        for value in [x, 1.0, 0.0, time] {
+            body.write_f32::<E>(value).unwrap();
+        }
+    }
+    rotation(body, 0.6, 0.8);
+    body.write_f32::<E>(0.5).unwrap();
+}
+
+/// A file holding a metadata chunk with the given version, an unknown chunk, a submotion
+/// chunk with the given version and a compressed bone animation chunk.
+pub(crate) fn file<E: ByteOrder>(
+    big_endian: bool,
+    metadata_version: i32,
+    submotion_version: i32,
+) -> Vec<u8> {
+    let mut file = b"XSM ".to_vec();
+    file.extend_from_slice(&[1, 0, big_endian as u8, 0]);
+
+    let mut metadata = vec![];
+    metadata.write_f32::<E>(1.0).unwrap();
+    metadata.write_f32::<E>(0.001).unwrap();
+    metadata.write_i32::<E>(30).unwrap();
+    metadata.extend_from_slice(&[2, 1, 0, 0]);
+    for text in ["3ds max", "run.max", "today", "run"] {
+        string::<E>(&mut metadata, text);
+    }
+    chunk::<E>(&mut file, 201, metadata_version, &metadata);
+    chunk::<E>(&mut file, 50, 1, &[1, 2, 3, 4, 5]);
+
+    let mut single = vec![];
+    submotion_body::<E>(&mut single, "Bip01", false);
+    chunk::<E>(&mut file, 200, submotion_version, &single);
+
+    let mut bone_animation = vec![];
+    bone_animation.write_i32::<E>(2).unwrap();
+    submotion_body::<E>(&mut bone_animation, "Bip01 Spine", true);
+    submotion_body::<E>(&mut bone_animation, "Bip01 Head", true);
+    chunk::<E>(&mut file, 202, 1, &bone_animation);
+    file
+}
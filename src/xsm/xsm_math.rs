@@ -0,0 +1,203 @@
+#![allow(dead_code)]
+
+use bevy::math::{Quat, Vec3};
+
+use crate::xsm::xsm_structs::{
+    XsmQuaternion, XsmQuaternion16, XsmRotKey, XsmRotation, XsmScaleRotKey, XsmSubMotion, XsmVec3d,
+};
+
+impl From<&XsmVec3d> for Vec3 {
+    fn from(value: &XsmVec3d) -> Self {
+        Vec3::new(value.x, value.y, value.z)
+    }
+}
+
+impl From<Vec3> for XsmVec3d {
+    fn from(value: Vec3) -> Self {
+        XsmVec3d {
+            x: value.x,
+            y: value.y,
+            z: value.z,
+        }
+    }
+}
+
+impl From<XsmQuaternion> for Quat {
+    fn from(value: XsmQuaternion) -> Self {
+        Quat::from_xyzw(value.x, value.y, value.z, value.w)
+    }
+}
+
+impl From<Quat> for XsmQuaternion {
+    fn from(value: Quat) -> Self {
+        XsmQuaternion {
+            x: value.x,
+            y: value.y,
+            z: value.z,
+            w: value.w,
+        }
+    }
+}
+
+impl XsmQuaternion {
+    pub(crate) const IDENTITY: XsmQuaternion = XsmQuaternion {
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+        w: 1.0,
+    };
+}
+
+impl XsmQuaternion16 {
+    /// Dequantizes the components to `[-1, 1]` and renormalizes the result.
+    /// An all zero quaternion decodes to the identity.
+    pub(crate) fn to_quaternion(self) -> XsmQuaternion {
+        let quat = Quat::from_xyzw(
+            self.x as f32 / 32767.0,
+            self.y as f32 / 32767.0,
+            self.z as f32 / 32767.0,
+            self.w as f32 / 32767.0,
+        );
+        if quat.length_squared() == 0.0 {
+            return XsmQuaternion::IDENTITY;
+        }
+        XsmQuaternion::from(quat.normalize())
+    }
+
+    pub(crate) fn from_quaternion(quaternion: XsmQuaternion) -> XsmQuaternion16 {
+        let quantize =
+            |component: f32| (component * 32767.0).round().clamp(-32767.0, 32767.0) as i16;
+        let quat = Quat::from(quaternion).normalize();
+        XsmQuaternion16 {
+            x: quantize(quat.x),
+            y: quantize(quat.y),
+            z: quantize(quat.z),
+            w: quantize(quat.w),
+        }
+    }
+}
+
+impl XsmRotation {
+    pub(crate) fn to_quaternion(self) -> XsmQuaternion {
+        match self {
+            XsmRotation::Compressed(quaternion) => quaternion.to_quaternion(),
+            XsmRotation::Uncompressed(quaternion) => {
+                let quat = Quat::from(quaternion);
+                if quat.length_squared() == 0.0 {
+                    return XsmQuaternion::IDENTITY;
+                }
+                XsmQuaternion::from(quat.normalize())
+            }
+        }
+    }
+
+    /// Stores `rotation` with the same encoding as this value.
+    pub(crate) fn with_quaternion(&self, rotation: XsmQuaternion) -> XsmRotation {
+        match self {
+            XsmRotation::Compressed(_) => {
+                XsmRotation::Compressed(XsmQuaternion16::from_quaternion(rotation))
+            }
+            XsmRotation::Uncompressed(_) => XsmRotation::Uncompressed(rotation),
+        }
+    }
+}
+
+impl XsmRotKey {
+    pub(crate) fn rotation(&self) -> XsmQuaternion {
+        self.rot.to_quaternion()
+    }
+
+    pub(crate) fn set_rotation(&mut self, rotation: XsmQuaternion) {
+        self.rot = self.rot.with_quaternion(rotation);
+    }
+}
+
+impl XsmScaleRotKey {
+    pub(crate) fn rotation(&self) -> XsmQuaternion {
+        self.rot.to_quaternion()
+    }
+
+    pub(crate) fn set_rotation(&mut self, rotation: XsmQuaternion) {
+        self.rot = self.rot.with_quaternion(rotation);
+    }
+}
+
+impl XsmSubMotion {
+    pub(crate) fn pose_rotation(&self) -> XsmQuaternion {
+        self.pose_rot.to_quaternion()
+    }
+
+    pub(crate) fn bind_pose_rotation(&self) -> XsmQuaternion {
+        self.bind_pose_rot.to_quaternion()
+    }
+
+    pub(crate) fn pose_scale_rotation(&self) -> XsmQuaternion {
+        self.pose_scale_rot.to_quaternion()
+    }
+
+    pub(crate) fn bind_pose_scale_rotation(&self) -> XsmQuaternion {
+        self.bind_pose_scale_rot.to_quaternion()
+    }
+
+    pub(crate) fn set_pose_rotation(&mut self, rotation: XsmQuaternion) {
+        self.pose_rot = self.pose_rot.with_quaternion(rotation);
+    }
+
+    pub(crate) fn set_bind_pose_rotation(&mut self, rotation: XsmQuaternion) {
+        self.bind_pose_rot = self.bind_pose_rot.with_quaternion(rotation);
+    }
+
+    pub(crate) fn set_pose_scale_rotation(&mut self, rotation: XsmQuaternion) {
+        self.pose_scale_rot = self.pose_scale_rot.with_quaternion(rotation);
+    }
+
+    pub(crate) fn set_bind_pose_scale_rotation(&mut self, rotation: XsmQuaternion) {
+        self.bind_pose_scale_rot = self.bind_pose_scale_rot.with_quaternion(rotation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quaternion16(x: i16, y: i16, z: i16, w: i16) -> XsmQuaternion16 {
+        XsmQuaternion16 { x, y, z, w }
+    }
+
+    #[test]
+    fn dequantizes_compressed_quaternions() {
+        let quaternion = quaternion16(0, 0, 0, 32767).to_quaternion();
+        assert!(Quat::from(quaternion).abs_diff_eq(Quat::IDENTITY, 1e-6));
+        let quaternion = quaternion16(-32767, 0, 0, 0).to_quaternion();
+        assert!(Quat::from(quaternion).abs_diff_eq(Quat::from_xyzw(-1.0, 0.0, 0.0, 0.0), 1e-6));
+    }
+
+    #[test]
+    fn renormalizes_compressed_quaternions() {
+        let quaternion = Quat::from(quaternion16(10000, 0, 0, 10000).to_quaternion());
+        assert!((quaternion.length() - 1.0).abs() < 1e-6);
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        assert!(quaternion.abs_diff_eq(Quat::from_xyzw(half, 0.0, 0.0, half), 1e-6));
+    }
+
+    #[test]
+    fn all_zero_quaternions_decode_to_identity() {
+        let quaternion = quaternion16(0, 0, 0, 0).to_quaternion();
+        assert!(Quat::from(quaternion).abs_diff_eq(Quat::IDENTITY, 0.0));
+        let rotation = XsmRotation::Uncompressed(XsmQuaternion {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            w: 0.0,
+        });
+        assert!(Quat::from(rotation.to_quaternion()).abs_diff_eq(Quat::IDENTITY, 0.0));
+    }
+
+    #[test]
+    fn quantizing_round_trips_within_one_step() {
+        let rotation = Quat::from_euler(bevy::math::EulerRot::XYZ, 0.3, -1.2, 2.0);
+        let quantized = XsmQuaternion16::from_quaternion(XsmQuaternion::from(rotation));
+        let restored = Quat::from(quantized.to_quaternion());
+        assert!(restored.abs_diff_eq(rotation, 1.0 / 32767.0));
+    }
+}
@@ -0,0 +1,271 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use bevy::math::{Quat, Vec3};
+
+use crate::xac::xac_structs::Xac;
+use crate::xsm::xsm_enums::XsmMirrorPlane;
+use crate::xsm::xsm_structs::{Xsm, XsmMirrorOptions, XsmQuaternion, XsmSubMotion, XsmVec3d};
+
+impl Default for XsmMirrorOptions {
+    /// Biped naming (`Bip01 L Hand` / `Bip01 R Hand`) mirrored across the YZ plane.
+    fn default() -> Self {
+        XsmMirrorOptions {
+            left_pattern: " L ".to_string(),
+            right_pattern: " R ".to_string(),
+            plane: XsmMirrorPlane::YZ,
+        }
+    }
+}
+
+impl XsmMirrorPlane {
+    fn normal(self) -> Vec3 {
+        match self {
+            XsmMirrorPlane::YZ => Vec3::X,
+            XsmMirrorPlane::XZ => Vec3::Y,
+            XsmMirrorPlane::XY => Vec3::Z,
+        }
+    }
+
+    fn reflect(self, value: Vec3) -> Vec3 {
+        value - 2.0 * value.dot(self.normal()) * self.normal()
+    }
+
+    /// A rotation conjugated by the reflection keeps its angle and gets its axis reflected
+    /// and negated, e.g. `(x, y, z, w)` becomes `(x, -y, -z, w)` for the YZ plane.
+    fn rotation(self, value: Quat) -> Quat {
+        let axis = -self.reflect(value.xyz());
+        Quat::from_xyzw(axis.x, axis.y, axis.z, value.w)
+    }
+}
+
+/// Replaces `from` with `to` in a bone name. Patterns with surrounding spaces also match
+/// at the start or end of the name, so `" L "` finds both `Bip01 L Hand` and `Hand L`.
+fn swap_side(node_name: &str, from: &str, to: &str) -> Option<String> {
+    if from.trim().is_empty() {
+        return None;
+    }
+    if node_name.contains(from) {
+        return Some(node_name.replacen(from, to, 1));
+    }
+    let suffix = from.trim_end();
+    if suffix.len() < from.len() && node_name.ends_with(suffix) {
+        let stem = &node_name[..node_name.len() - suffix.len()];
+        return Some(format!("{}{}", stem, to.trim_end()));
+    }
+    let prefix = from.trim_start();
+    if prefix.len() < from.len() && node_name.starts_with(prefix) {
+        return Some(format!("{}{}", to.trim_start(), &node_name[prefix.len()..]));
+    }
+    None
+}
+
+impl XsmMirrorOptions {
+    /// Swaps the left and right pattern in a bone name, leaving other names untouched.
+    pub(crate) fn mirror_name(&self, node_name: &str) -> String {
+        swap_side(node_name, &self.left_pattern, &self.right_pattern)
+            .or_else(|| swap_side(node_name, &self.right_pattern, &self.left_pattern))
+            .unwrap_or_else(|| node_name.to_string())
+    }
+}
+
+/// Per bone rotations that turn a reflected bind frame into the bind frame of the bone
+/// on the other side. Biped rigs do not build their left and right frames as mirror
+/// images of each other, so a reflected left rotation has to be re-expressed in the
+/// frame of the right bone.
+struct MirrorFrames {
+    plane: XsmMirrorPlane,
+    /// For each bone: the correction of its own frame and of its parent's frame.
+    corrections: HashMap<String, (Quat, Quat)>,
+}
+
+impl MirrorFrames {
+    fn new(xac: &Xac, options: &XsmMirrorOptions) -> MirrorFrames {
+        let nodes = &xac.node_hierarchy.node_data;
+        let world: Vec<Quat> = xac
+            .node_world_matrices()
+            .iter()
+            .map(|matrix| matrix.to_scale_rotation_translation().1)
+            .collect();
+        let find = |node_name: &str| nodes.iter().position(|node| node.name == node_name);
+        let correction = |index: usize| {
+            let other = find(&options.mirror_name(&nodes[index].name)).unwrap_or(index);
+            (options.plane.rotation(world[index]).inverse() * world[other]).normalize()
+        };
+        let corrections = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| {
+                let parent = node.parent_node_id;
+                let parent_correction = if parent >= 0 && (parent as usize) < nodes.len() {
+                    correction(parent as usize)
+                } else {
+                    Quat::IDENTITY
+                };
+                (node.name.clone(), (correction(index), parent_correction))
+            })
+            .collect();
+        MirrorFrames {
+            plane: options.plane,
+            corrections,
+        }
+    }
+
+    fn corrections(&self, node_name: &str) -> (Quat, Quat) {
+        self.corrections
+            .get(node_name)
+            .copied()
+            .unwrap_or((Quat::IDENTITY, Quat::IDENTITY))
+    }
+}
+
+impl XsmSubMotion {
+    fn mirror(&mut self, frames: &MirrorFrames, options: &XsmMirrorOptions) {
+        let plane = frames.plane;
+        let (own, parent) = frames.corrections(&self.node_name);
+        let rotation = |value: XsmQuaternion| {
+            XsmQuaternion::from(
+                (parent.inverse() * plane.rotation(Quat::from(value)) * own).normalize(),
+            )
+        };
+        let scale_rotation = |value: XsmQuaternion| {
+            XsmQuaternion::from(
+                (own.inverse() * plane.rotation(Quat::from(value)) * own).normalize(),
+            )
+        };
+        let position = |value: &mut XsmVec3d| {
+            *value = XsmVec3d::from(parent.inverse() * plane.reflect(Vec3::from(&*value)));
+        };
+        self.node_name = options.mirror_name(&self.node_name);
+        self.set_pose_rotation(rotation(self.pose_rotation()));
+        self.set_bind_pose_rotation(rotation(self.bind_pose_rotation()));
+        self.set_pose_scale_rotation(scale_rotation(self.pose_scale_rotation()));
+        self.set_bind_pose_scale_rotation(scale_rotation(self.bind_pose_scale_rotation()));
+        position(&mut self.pose_pos);
+        position(&mut self.bind_pose_pos);
+        for key in self.pos_key.iter_mut() {
+            position(&mut key.pos);
+        }
+        for key in self.rot_key.iter_mut() {
+            key.set_rotation(rotation(key.rotation()));
+        }
+        for key in self.scale_rot_key.iter_mut() {
+            key.set_rotation(scale_rotation(key.rotation()));
+        }
+    }
+}
+
+impl Xsm {
+    /// Returns a copy with left and right tracks swapped by name and every bone moved to
+    /// the mirror image of its pose across `options.plane`, in the space of `xac`.
+    /// Rotations are re-expressed in the bind frame of the bone on the other side, so rigs
+    /// whose left and right frames are not mirror images of each other mirror correctly.
+    pub(crate) fn mirrored(&self, xac: &Xac, options: &XsmMirrorOptions) -> Xsm {
+        let frames = MirrorFrames::new(xac, options);
+        let mut mirrored = self.clone();
+        for submotion in mirrored.bone_animation.skeletal_submotion.iter_mut() {
+            submotion.mirror(&frames, options);
+        }
+        mirrored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+    use crate::xac::xac_fixtures::{actor, node};
+    use crate::xsm::xsm_fixtures::motion;
+
+    /// A root with two arms whose right frame is the reflected left frame turned half way
+    /// around x, the way Biped builds its sides.
+    fn biped() -> Xac {
+        let left = Quat::from_rotation_z(0.4) * Quat::from_rotation_y(0.3);
+        let right = XsmMirrorPlane::YZ.rotation(left) * Quat::from_rotation_x(PI);
+        actor(vec![
+            node("Bip01", -1, Vec3::ZERO, Quat::IDENTITY),
+            node("Bip01 L UpperArm", 0, Vec3::new(1.0, 0.0, 0.0), left),
+            node("Bip01 R UpperArm", 0, Vec3::new(-1.0, 0.0, 0.0), right),
+        ])
+    }
+
+    fn same_rotation(a: XsmQuaternion, b: Quat) -> bool {
+        Quat::from(a).angle_between(b) < 1e-3
+    }
+
+    #[test]
+    fn mirroring_twice_restores_the_motion() {
+        let xac = biped();
+        let xsm = motion(&["Bip01", "Bip01 L UpperArm", "Bip01 R UpperArm"], 5);
+        let options = XsmMirrorOptions::default();
+        let twice = xsm.mirrored(&xac, &options).mirrored(&xac, &options);
+        let pairs = xsm
+            .bone_animation
+            .skeletal_submotion
+            .iter()
+            .zip(twice.bone_animation.skeletal_submotion.iter());
+        for (original, restored) in pairs {
+            assert_eq!(original.node_name, restored.node_name);
+            for (a, b) in original.rot_key.iter().zip(restored.rot_key.iter()) {
+                assert!(same_rotation(b.rotation(), a.rotation().into()));
+            }
+            for (a, b) in original.pos_key.iter().zip(restored.pos_key.iter()) {
+                assert!(Vec3::from(&a.pos).abs_diff_eq(Vec3::from(&b.pos), 1e-5));
+            }
+        }
+    }
+
+    #[test]
+    fn swaps_left_and_right_tracks() {
+        let xac = actor(vec![
+            node("Bip01", -1, Vec3::ZERO, Quat::IDENTITY),
+            node("Bip01 L Hand", 0, Vec3::X, Quat::IDENTITY),
+            node("Bip01 R Hand", 0, -Vec3::X, Quat::IDENTITY),
+        ]);
+        let xsm = motion(&["Bip01 L Hand", "Bip01 R Hand", "Bip01"], 3);
+        let mirrored = xsm.mirrored(&xac, &XsmMirrorOptions::default());
+        let submotions = &mirrored.bone_animation.skeletal_submotion;
+        assert_eq!(submotions[0].node_name, "Bip01 R Hand");
+        assert_eq!(submotions[1].node_name, "Bip01 L Hand");
+        assert_eq!(submotions[2].node_name, "Bip01");
+        let key = &submotions[0].pos_key[2];
+        assert!(Vec3::from(&key.pos).abs_diff_eq(Vec3::new(-4.0 / 30.0, 1.0, 0.0), 1e-6));
+        let rotation = Quat::from_rotation_y(-2.0 / 30.0);
+        assert!(same_rotation(submotions[0].rot_key[2].rotation(), rotation));
+    }
+
+    #[test]
+    fn swaps_side_suffixes_and_prefixes() {
+        let options = XsmMirrorOptions::default();
+        assert_eq!(options.mirror_name("Bip01 L Hand"), "Bip01 R Hand");
+        assert_eq!(options.mirror_name("Hand L"), "Hand R");
+        assert_eq!(options.mirror_name("R Hand"), "L Hand");
+        assert_eq!(options.mirror_name("Bip01 Spine"), "Bip01 Spine");
+    }
+
+    #[test]
+    fn mirrored_left_bind_pose_is_the_right_bind_pose() {
+        let xac = biped();
+        let nodes = &xac.node_hierarchy.node_data;
+        let mut xsm = motion(&["Bip01 L UpperArm"], 2);
+        let submotion = &mut xsm.bone_animation.skeletal_submotion[0];
+        for key in submotion.rot_key.iter_mut() {
+            key.set_rotation(XsmQuaternion::from(Quat::from(&nodes[1].rotation)));
+        }
+        for key in submotion.pos_key.iter_mut() {
+            key.pos = XsmVec3d::from(Vec3::from(&nodes[1].position));
+        }
+        let mirrored = xsm.mirrored(&xac, &XsmMirrorOptions::default());
+        let submotion = &mirrored.bone_animation.skeletal_submotion[0];
+        assert_eq!(submotion.node_name, "Bip01 R UpperArm");
+        let right = Quat::from(&nodes[2].rotation);
+        for key in submotion.rot_key.iter() {
+            assert!(same_rotation(key.rotation(), right));
+        }
+        for key in submotion.pos_key.iter() {
+            assert!(Vec3::from(&key.pos).abs_diff_eq(Vec3::from(&nodes[2].position), 1e-6));
+        }
+    }
+}
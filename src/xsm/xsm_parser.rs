@@ -1,177 +1,253 @@
 #![allow(dead_code)]
 use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom};
 
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt};
 
-use crate::xsm::xsm_enums::XsmChunkType::{XsmBoneAnimationId, XsmMetadataId};
+use crate::xsm::xsm_enums::XsmChunkType::{XsmBoneAnimationId, XsmMetadataId, XsmSubMotionId};
+use crate::xsm::xsm_enums::XsmError;
 use crate::xsm::xsm_structs::{
-    Xsm, XsmBoneAnimation, XsmChunk, XsmHeader, XsmMetadata, XsmPosKey, XsmQuaternion16, XsmRotKey,
-    XsmScaleKey, XsmScaleRotKey, XsmSubMotion, XsmVec3d,
+    Xsm, XsmBoneAnimation, XsmChunk, XsmHeader, XsmMetadata, XsmPosKey, XsmQuaternion,
+    XsmQuaternion16, XsmRotKey, XsmRotation, XsmScaleKey, XsmScaleRotKey, XsmSubMotion, XsmVec3d,
 };
 
-pub fn xsmparse(path: &str) -> Xsm {
-    let mut xsm_file = File::open(path).expect("Cannot open xsm file!");
-    let mut xsm_new = Xsm {
-        header: XsmHeader {
-            magic: "".to_string(),
-            major_version: 0,
-            minor_version: 0,
-            big_endian: false,
-        },
-        metadata: XsmMetadata {
-            unused: 0.0,
-            max_acceptable_error: 0.0,
-            fps: 0,
-            exporter_major_version: 0,
-            exporter_minor_version: 0,
-            source_app: "".to_string(),
-            original_filename: "".to_string(),
-            export_date: "".to_string(),
-            motion_name: "".to_string(),
-        },
-        bone_animation: XsmBoneAnimation {
-            num_submotion: 0,
-            skeletal_submotion: vec![],
-        },
-    };
-    read_header(&mut xsm_file, &mut xsm_new);
-    if xsm_new.header.magic == "XSM ".to_string() {
-        read_chunk(&mut xsm_file, &mut xsm_new);
-    } else {
-        panic!("Wrong file header!");
+pub fn xsmparse(path: &str) -> Result<Xsm, XsmError> {
+    let xsm_file = File::open(path)?;
+    Xsm::from_reader(BufReader::new(xsm_file))
+}
+
+impl Xsm {
+    pub fn from_reader<R: Read + Seek>(mut reader: R) -> Result<Xsm, XsmError> {
+        let header = read_header(&mut reader)?;
+        if header.magic != "XSM " {
+            return Err(XsmError::InvalidMagic(header.magic));
+        }
+        let mut xsm_new = Xsm {
+            header,
+            metadata: XsmMetadata {
+                unused: 0.0,
+                max_acceptable_error: 0.0,
+                fps: 0,
+                exporter_major_version: 0,
+                exporter_minor_version: 0,
+                source_app: "".to_string(),
+                original_filename: "".to_string(),
+                export_date: "".to_string(),
+                motion_name: "".to_string(),
+            },
+            bone_animation: XsmBoneAnimation {
+                num_submotion: 0,
+                skeletal_submotion: vec![],
+            },
+            chunks: vec![],
+            events: vec![],
+        };
+        if xsm_new.header.big_endian {
+            read_chunk::<BigEndian, R>(&mut reader, &mut xsm_new)?;
+        } else {
+            read_chunk::<LittleEndian, R>(&mut reader, &mut xsm_new)?;
+        }
+        Ok(xsm_new)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Xsm, XsmError> {
+        Xsm::from_reader(Cursor::new(bytes))
     }
-    xsm_new
 }
-fn xsm_read_string(file: &mut File) -> String {
+
+pub(crate) fn xsm_read_string<E: ByteOrder, R: Read>(file: &mut R) -> Result<String, XsmError> {
     let mut text = String::new();
-    let length = file.read_i32::<LittleEndian>().unwrap();
+    let length = file.read_i32::<E>()?;
     for _ in 0..length {
-        let character = file.read_u8().unwrap();
+        let character = file.read_u8()?;
         text.push(character as char);
     }
-    text
+    Ok(text)
 }
 
-fn xsm_read_quaternion16(file: &mut File) -> XsmQuaternion16 {
-    let quat = XsmQuaternion16 {
-        x: file.read_i16::<LittleEndian>().unwrap(),
-        y: file.read_i16::<LittleEndian>().unwrap(),
-        z: file.read_i16::<LittleEndian>().unwrap(),
-        w: file.read_i16::<LittleEndian>().unwrap(),
-    };
-    quat
+fn xsm_read_quaternion16<E: ByteOrder, R: Read>(file: &mut R) -> Result<XsmQuaternion16, XsmError> {
+    Ok(XsmQuaternion16 {
+        x: file.read_i16::<E>()?,
+        y: file.read_i16::<E>()?,
+        z: file.read_i16::<E>()?,
+        w: file.read_i16::<E>()?,
+    })
 }
 
-fn xsm_read_vec3d(file: &mut File) -> XsmVec3d {
-    let vec3d = XsmVec3d {
-        x: file.read_f32::<LittleEndian>().unwrap(),
-        y: file.read_f32::<LittleEndian>().unwrap(),
-        z: file.read_f32::<LittleEndian>().unwrap(),
-    };
-    vec3d
+fn xsm_read_quaternion<E: ByteOrder, R: Read>(file: &mut R) -> Result<XsmQuaternion, XsmError> {
+    Ok(XsmQuaternion {
+        x: file.read_f32::<E>()?,
+        y: file.read_f32::<E>()?,
+        z: file.read_f32::<E>()?,
+        w: file.read_f32::<E>()?,
+    })
+}
+
+fn xsm_read_rotation<E: ByteOrder, R: Read>(
+    file: &mut R,
+    compressed: bool,
+) -> Result<XsmRotation, XsmError> {
+    if compressed {
+        Ok(XsmRotation::Compressed(xsm_read_quaternion16::<E, R>(
+            file,
+        )?))
+    } else {
+        Ok(XsmRotation::Uncompressed(xsm_read_quaternion::<E, R>(
+            file,
+        )?))
+    }
+}
+
+fn xsm_read_vec3d<E: ByteOrder, R: Read>(file: &mut R) -> Result<XsmVec3d, XsmError> {
+    Ok(XsmVec3d {
+        x: file.read_f32::<E>()?,
+        y: file.read_f32::<E>()?,
+        z: file.read_f32::<E>()?,
+    })
 }
 
-fn read_header<'a>(file: &'a mut File, xsm: &'a mut Xsm) -> &'a mut Xsm {
+/// Reads the 8 byte header shared by XSM and XPM files. The magic is checked by the caller.
+pub(crate) fn read_header<R: Read>(file: &mut R) -> Result<XsmHeader, XsmError> {
     let mut magic = [0; 4];
-    file.read_exact(&mut magic).unwrap();
-    xsm.header.magic = std::str::from_utf8(&magic).unwrap().to_string();
-    xsm.header.major_version = file.read_u8().unwrap();
-    xsm.header.minor_version = file.read_u8().unwrap();
-    xsm.header.big_endian = file.read_u8().unwrap() != 0;
-    file.read_u8().unwrap(); // Padding
-    xsm
+    file.read_exact(&mut magic)?;
+    let header = XsmHeader {
+        magic: String::from_utf8_lossy(&magic).to_string(),
+        major_version: file.read_u8()?,
+        minor_version: file.read_u8()?,
+        big_endian: file.read_u8()? != 0,
+    };
+    file.read_u8()?; // Padding
+    Ok(header)
 }
 
-fn read_chunk<'a>(file: &'a mut File, xsm: &'a mut Xsm) -> &'a mut Xsm {
-    while file.stream_position().unwrap() < file.metadata().unwrap().len() {
-        let chunk = XsmChunk {
-            chunk_type: file.read_i32::<LittleEndian>().unwrap(),
-            length: file.read_i32::<LittleEndian>().unwrap(),
-            version: file.read_i32::<LittleEndian>().unwrap(),
+fn read_chunk<E: ByteOrder, R: Read + Seek>(file: &mut R, xsm: &mut Xsm) -> Result<(), XsmError> {
+    let mut position = file.stream_position()?;
+    let end = file.seek(SeekFrom::End(0))?;
+    file.seek(SeekFrom::Start(position))?;
+    while position < end {
+        let mut chunk = XsmChunk {
+            chunk_type: file.read_i32::<E>()?,
+            length: file.read_i32::<E>()?,
+            version: file.read_i32::<E>()?,
+            data: vec![],
         };
-        let position = file.stream_position().unwrap();
+        position = file.stream_position()?;
+        if chunk.length < 0 || position + chunk.length as u64 > end {
+            return Err(XsmError::InvalidChunkLength {
+                chunk_type: chunk.chunk_type,
+                length: chunk.length,
+            });
+        }
         if chunk.chunk_type == XsmMetadataId as i32 {
-            read_metadata(file, xsm);
+            read_metadata::<E, R>(file, xsm)?;
+        }
+        if is_known_submotion_chunk(&chunk) {
+            let submotion = read_submotion::<E, R>(file, false)?;
+            xsm.bone_animation.skeletal_submotion.push(submotion);
+            xsm.bone_animation.num_submotion = xsm.bone_animation.skeletal_submotion.len() as i32;
         }
         if chunk.chunk_type == XsmBoneAnimationId as i32 {
-            read_bone_animation(file, xsm);
+            read_bone_animation::<E, R>(file, xsm)?;
+        }
+        // Keep whatever the chunk holds beyond the decoded fields so it can be written back.
+        let consumed = file.stream_position()? - position;
+        if consumed < chunk.length as u64 {
+            chunk.data = vec![0; (chunk.length as u64 - consumed) as usize];
+            file.read_exact(&mut chunk.data)?;
         }
-        file.seek(SeekFrom::Start(position + chunk.length as u64))
-            .unwrap();
+        position = file.seek(SeekFrom::Start(position + chunk.length as u64))?;
+        xsm.chunks.push(chunk);
     }
-    xsm
+    Ok(())
 }
 
-fn read_metadata<'a>(file: &'a mut File, xsm: &'a mut Xsm) -> &'a mut Xsm {
-    xsm.metadata.unused = file.read_f32::<LittleEndian>().unwrap();
-    xsm.metadata.max_acceptable_error = file.read_f32::<LittleEndian>().unwrap();
-    xsm.metadata.fps = file.read_i32::<LittleEndian>().unwrap();
-    xsm.metadata.exporter_major_version = file.read_u8().unwrap();
-    xsm.metadata.exporter_minor_version = file.read_u8().unwrap();
-    file.read_u8().unwrap(); //Padding
-    file.read_u8().unwrap(); //Padding
-    xsm.metadata.source_app = xsm_read_string(file);
-    xsm.metadata.original_filename = xsm_read_string(file);
-    xsm.metadata.export_date = xsm_read_string(file);
-    xsm.metadata.motion_name = xsm_read_string(file);
-    xsm
+/// No public description of the Tree of Savior XSM layouts has been found. The metadata
+/// and bone animation chunks are read with the single layout this parser has always used,
+/// whatever their version. A submotion chunk (200) holds one submotion with float
+/// quaternions; only version 1 is read and other versions are kept as raw data.
+pub(crate) fn is_known_submotion_chunk(chunk: &XsmChunk) -> bool {
+    chunk.chunk_type == XsmSubMotionId as i32 && chunk.version == 1
 }
 
-fn read_bone_animation<'a>(file: &'a mut File, xsm: &'a mut Xsm) -> &'a mut Xsm {
-    xsm.bone_animation.num_submotion = file.read_i32::<LittleEndian>().unwrap();
-    for _ in 0..xsm.bone_animation.num_submotion {
-        xsm.bone_animation.skeletal_submotion.push({
-            let mut submotion = XsmSubMotion {
-                pose_rot: xsm_read_quaternion16(file),
-                bind_pose_rot: xsm_read_quaternion16(file),
-                pose_scale_rot: xsm_read_quaternion16(file),
-                bind_pose_scale_rot: xsm_read_quaternion16(file),
-                pose_pos: xsm_read_vec3d(file),
-                pose_scale: xsm_read_vec3d(file),
-                bind_pose_pos: xsm_read_vec3d(file),
-                bind_pose_scale_pos: xsm_read_vec3d(file),
-                num_pos_keys: file.read_i32::<LittleEndian>().unwrap(),
-                num_rot_keys: file.read_i32::<LittleEndian>().unwrap(),
-                num_scale_keys: file.read_i32::<LittleEndian>().unwrap(),
-                num_scale_rot_keys: file.read_i32::<LittleEndian>().unwrap(),
-                max_error: file.read_f32::<LittleEndian>().unwrap(),
-                node_name: xsm_read_string(file),
-                pos_key: vec![],
-                rot_key: vec![],
-                scale_key: vec![],
-                scale_rot_key: vec![],
-            };
-
-            for _ in 0..submotion.num_pos_keys {
-                submotion.pos_key.push(XsmPosKey {
-                    pos: xsm_read_vec3d(file),
-                    time: file.read_f32::<LittleEndian>().unwrap(),
-                })
-            }
-
-            for _ in 0..submotion.num_rot_keys {
-                submotion.rot_key.push(XsmRotKey {
-                    rot: xsm_read_quaternion16(file),
-                    time: file.read_f32::<LittleEndian>().unwrap(),
-                })
-            }
-            for _ in 0..submotion.num_scale_keys {
-                submotion.scale_key.push(XsmScaleKey {
-                    scale: xsm_read_vec3d(file),
-                    time: file.read_f32::<LittleEndian>().unwrap(),
-                })
-            }
-
-            for _ in 0..submotion.num_scale_rot_keys {
-                submotion.scale_rot_key.push(XsmScaleRotKey {
-                    rot: xsm_read_quaternion16(file),
-                    time: file.read_f32::<LittleEndian>().unwrap(),
-                })
-            }
-            submotion
-        });
+fn read_metadata<E: ByteOrder, R: Read>(file: &mut R, xsm: &mut Xsm) -> Result<(), XsmError> {
+    xsm.metadata.unused = file.read_f32::<E>()?;
+    xsm.metadata.max_acceptable_error = file.read_f32::<E>()?;
+    xsm.metadata.fps = file.read_i32::<E>()?;
+    xsm.metadata.exporter_major_version = file.read_u8()?;
+    xsm.metadata.exporter_minor_version = file.read_u8()?;
+    file.read_u8()?; //Padding
+    file.read_u8()?; //Padding
+    xsm.metadata.source_app = xsm_read_string::<E, R>(file)?;
+    xsm.metadata.original_filename = xsm_read_string::<E, R>(file)?;
+    xsm.metadata.export_date = xsm_read_string::<E, R>(file)?;
+    xsm.metadata.motion_name = xsm_read_string::<E, R>(file)?;
+    Ok(())
+}
+
+fn read_submotion<E: ByteOrder, R: Read>(
+    file: &mut R,
+    compressed: bool,
+) -> Result<XsmSubMotion, XsmError> {
+    let mut submotion = XsmSubMotion {
+        pose_rot: xsm_read_rotation::<E, R>(file, compressed)?,
+        bind_pose_rot: xsm_read_rotation::<E, R>(file, compressed)?,
+        pose_scale_rot: xsm_read_rotation::<E, R>(file, compressed)?,
+        bind_pose_scale_rot: xsm_read_rotation::<E, R>(file, compressed)?,
+        pose_pos: xsm_read_vec3d::<E, R>(file)?,
+        pose_scale: xsm_read_vec3d::<E, R>(file)?,
+        bind_pose_pos: xsm_read_vec3d::<E, R>(file)?,
+        bind_pose_scale_pos: xsm_read_vec3d::<E, R>(file)?,
+        num_pos_keys: file.read_i32::<E>()?,
+        num_rot_keys: file.read_i32::<E>()?,
+        num_scale_keys: file.read_i32::<E>()?,
+        num_scale_rot_keys: file.read_i32::<E>()?,
+        max_error: 0.0,
+        node_name: "".to_string(),
+        pos_key: vec![],
+        rot_key: vec![],
+        scale_key: vec![],
+        scale_rot_key: vec![],
+    };
+    if compressed {
+        submotion.max_error = file.read_f32::<E>()?;
     }
+    submotion.node_name = xsm_read_string::<E, R>(file)?;
 
-    xsm
+    for _ in 0..submotion.num_pos_keys {
+        submotion.pos_key.push(XsmPosKey {
+            pos: xsm_read_vec3d::<E, R>(file)?,
+            time: file.read_f32::<E>()?,
+        })
+    }
+
+    for _ in 0..submotion.num_rot_keys {
+        submotion.rot_key.push(XsmRotKey {
+            rot: xsm_read_rotation::<E, R>(file, compressed)?,
+            time: file.read_f32::<E>()?,
+        })
+    }
+    for _ in 0..submotion.num_scale_keys {
+        submotion.scale_key.push(XsmScaleKey {
+            scale: xsm_read_vec3d::<E, R>(file)?,
+            time: file.read_f32::<E>()?,
+        })
+    }
+
+    for _ in 0..submotion.num_scale_rot_keys {
+        submotion.scale_rot_key.push(XsmScaleRotKey {
+            rot: xsm_read_rotation::<E, R>(file, compressed)?,
+            time: file.read_f32::<E>()?,
+        })
+    }
+    Ok(submotion)
+}
+
+fn read_bone_animation<E: ByteOrder, R: Read>(file: &mut R, xsm: &mut Xsm) -> Result<(), XsmError> {
+    let num_submotion = file.read_i32::<E>()?;
+    for _ in 0..num_submotion {
+        let submotion = read_submotion::<E, R>(file, true)?;
+        xsm.bone_animation.skeletal_submotion.push(submotion);
+    }
+    xsm.bone_animation.num_submotion = xsm.bone_animation.skeletal_submotion.len() as i32;
+    Ok(())
 }
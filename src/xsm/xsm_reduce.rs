@@ -0,0 +1,219 @@
+#![allow(dead_code)]
+
+use bevy::math::{Quat, Vec3};
+
+use crate::xsm::xsm_structs::{Xsm, XsmReductionReport, XsmSubMotion, XsmTrackReduction};
+
+/// Greedily drops keys that linear interpolation between the surrounding kept keys
+/// reproduces within `max_error`. The first and last keys are always kept.
+fn reduce_track<T>(
+    keys: &mut Vec<T>,
+    time: impl Fn(&T) -> f32,
+    error: impl Fn(&T, &T, f32, &T) -> f32,
+    max_error: f32,
+) -> usize {
+    if keys.len() < 3 {
+        return 0;
+    }
+    let mut keep = vec![false; keys.len()];
+    keep[0] = true;
+    keep[keys.len() - 1] = true;
+    let mut anchor = 0;
+    let mut end = 2;
+    while end < keys.len() {
+        let span = time(&keys[end]) - time(&keys[anchor]);
+        let fits = (anchor + 1..end).all(|index| {
+            let factor = if span > 0.0 {
+                (time(&keys[index]) - time(&keys[anchor])) / span
+            } else {
+                0.0
+            };
+            error(&keys[anchor], &keys[end], factor, &keys[index]) <= max_error
+        });
+        if fits {
+            end += 1;
+        } else {
+            anchor = end - 1;
+            keep[anchor] = true;
+            end = anchor + 2;
+        }
+    }
+    let before = keys.len();
+    let mut index = 0;
+    keys.retain(|_| {
+        index += 1;
+        keep[index - 1]
+    });
+    before - keys.len()
+}
+
+fn vec3_error(a: Vec3, b: Vec3, factor: f32, key: Vec3) -> f32 {
+    a.lerp(b, factor).distance(key)
+}
+
+fn quat_error(a: Quat, b: Quat, factor: f32, key: Quat) -> f32 {
+    a.slerp(b, factor).angle_between(key)
+}
+
+impl XsmSubMotion {
+    /// Removes redundant keys while keeping every channel within `max_error`, measured in
+    /// units for positions and scales and in radians for rotations.
+    pub(crate) fn reduce_keys(&mut self, max_error: f32) -> XsmTrackReduction {
+        let report = XsmTrackReduction {
+            node_name: self.node_name.clone(),
+            pos_keys_removed: reduce_track(
+                &mut self.pos_key,
+                |key| key.time,
+                |a, b, factor, key| {
+                    vec3_error(
+                        Vec3::from(&a.pos),
+                        Vec3::from(&b.pos),
+                        factor,
+                        Vec3::from(&key.pos),
+                    )
+                },
+                max_error,
+            ),
+            rot_keys_removed: reduce_track(
+                &mut self.rot_key,
+                |key| key.time,
+                |a, b, factor, key| {
+                    quat_error(
+                        Quat::from(a.rotation()),
+                        Quat::from(b.rotation()),
+                        factor,
+                        Quat::from(key.rotation()),
+                    )
+                },
+                max_error,
+            ),
+            scale_keys_removed: reduce_track(
+                &mut self.scale_key,
+                |key| key.time,
+                |a, b, factor, key| {
+                    vec3_error(
+                        Vec3::from(&a.scale),
+                        Vec3::from(&b.scale),
+                        factor,
+                        Vec3::from(&key.scale),
+                    )
+                },
+                max_error,
+            ),
+            scale_rot_keys_removed: reduce_track(
+                &mut self.scale_rot_key,
+                |key| key.time,
+                |a, b, factor, key| {
+                    quat_error(
+                        Quat::from(a.rotation()),
+                        Quat::from(b.rotation()),
+                        factor,
+                        Quat::from(key.rotation()),
+                    )
+                },
+                max_error,
+            ),
+        };
+        self.num_pos_keys = self.pos_key.len() as i32;
+        self.num_rot_keys = self.rot_key.len() as i32;
+        self.num_scale_keys = self.scale_key.len() as i32;
+        self.num_scale_rot_keys = self.scale_rot_key.len() as i32;
+        report
+    }
+}
+
+impl Xsm {
+    /// Reduces every track using its own `max_error`, or the motion wide
+    /// `max_acceptable_error` when the track has none.
+    pub(crate) fn reduce_keys(&mut self) -> XsmReductionReport {
+        let fallback = self.metadata.max_acceptable_error;
+        XsmReductionReport {
+            tracks: self
+                .bone_animation
+                .skeletal_submotion
+                .iter_mut()
+                .map(|submotion| {
+                    let max_error = if submotion.max_error > 0.0 {
+                        submotion.max_error
+                    } else {
+                        fallback
+                    };
+                    submotion.reduce_keys(max_error)
+                })
+                .collect(),
+        }
+    }
+
+    pub(crate) fn reduce_keys_with_error(&mut self, max_error: f32) -> XsmReductionReport {
+        XsmReductionReport {
+            tracks: self
+                .bone_animation
+                .skeletal_submotion
+                .iter_mut()
+                .map(|submotion| submotion.reduce_keys(max_error))
+                .collect(),
+        }
+    }
+}
+
+impl XsmReductionReport {
+    pub(crate) fn total_removed(&self) -> usize {
+        self.tracks
+            .iter()
+            .map(|track| {
+                track.pos_keys_removed
+                    + track.rot_keys_removed
+                    + track.scale_keys_removed
+                    + track.scale_rot_keys_removed
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xsm::xsm_fixtures::{motion, vec3d};
+
+    #[test]
+    fn drops_keys_on_a_straight_line() {
+        let mut xsm = motion(&["Bip01"], 10);
+        let report = xsm.reduce_keys();
+        let submotion = &xsm.bone_animation.skeletal_submotion[0];
+        assert_eq!(report.tracks[0].pos_keys_removed, 8);
+        assert_eq!(submotion.num_pos_keys, 2);
+        assert_eq!(submotion.pos_key[0].time, 0.0);
+        assert!((submotion.pos_key[1].time - 0.3).abs() < 1e-6);
+        assert_eq!(
+            report.total_removed(),
+            report.tracks[0].pos_keys_removed + report.tracks[0].rot_keys_removed
+        );
+    }
+
+    #[test]
+    fn keeps_keys_outside_the_error() {
+        let mut xsm = motion(&["Bip01"], 10);
+        xsm.bone_animation.skeletal_submotion[0].pos_key[5].pos = vec3d(1.0, 2.0, 0.0);
+        xsm.reduce_keys_with_error(0.01);
+        let times: Vec<f32> = xsm.bone_animation.skeletal_submotion[0]
+            .pos_key
+            .iter()
+            .map(|key| (key.time * 30.0).round())
+            .collect();
+        assert_eq!(times, vec![0.0, 4.0, 5.0, 6.0, 9.0]);
+    }
+
+    #[test]
+    fn sampling_stays_within_the_error() {
+        let original = motion(&["Bip01"], 10);
+        let mut reduced = original.clone();
+        reduced.reduce_keys_with_error(0.01);
+        for frame in 0..10 {
+            let time = frame as f32 / 30.0;
+            let a = original.sample(time).nodes.remove(0);
+            let b = reduced.sample(time).nodes.remove(0);
+            assert!(Vec3::from(&a.position).distance(Vec3::from(&b.position)) <= 0.01);
+            assert!(Quat::from(a.rotation).angle_between(Quat::from(b.rotation)) <= 0.01);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+#![allow(dead_code)]
+
+use crate::xsm::xsm_structs::{
+    Xsm, XsmPosKey, XsmRotKey, XsmScaleKey, XsmScaleRotKey, XsmSubMotion,
+};
+
+/// Frame times from zero to `duration`. The last frame lands exactly on `duration` even
+/// when it is not a whole number of frames.
+fn frame_times(duration: f32, fps: i32) -> Vec<f32> {
+    let frame_count = (duration * fps as f32).ceil() as usize;
+    (0..=frame_count)
+        .map(|frame| (frame as f32 / fps as f32).min(duration))
+        .collect()
+}
+
+impl XsmSubMotion {
+    /// Replaces every channel with one key per entry of `times`.
+    fn bake(&mut self, times: &[f32]) {
+        let poses: Vec<_> = times.iter().map(|&time| self.sample(time)).collect();
+        self.pos_key = times
+            .iter()
+            .zip(poses.iter())
+            .map(|(&time, pose)| XsmPosKey {
+                pos: pose.position,
+                time,
+            })
+            .collect();
+        self.rot_key = times
+            .iter()
+            .zip(poses.iter())
+            .map(|(&time, pose)| XsmRotKey {
+                rot: self.pose_rot.with_quaternion(pose.rotation),
+                time,
+            })
+            .collect();
+        self.scale_key = times
+            .iter()
+            .zip(poses.iter())
+            .map(|(&time, pose)| XsmScaleKey {
+                scale: pose.scale,
+                time,
+            })
+            .collect();
+        self.scale_rot_key = times
+            .iter()
+            .zip(poses.iter())
+            .map(|(&time, pose)| XsmScaleRotKey {
+                rot: self.pose_scale_rot.with_quaternion(pose.scale_rotation),
+                time,
+            })
+            .collect();
+        self.num_pos_keys = self.pos_key.len() as i32;
+        self.num_rot_keys = self.rot_key.len() as i32;
+        self.num_scale_keys = self.scale_key.len() as i32;
+        self.num_scale_rot_keys = self.scale_rot_key.len() as i32;
+    }
+}
+
+impl Xsm {
+    /// Bakes every submotion to uniform frames at `fps`, or at the motion's own frame rate
+    /// when none is given. Each channel gets one key per frame and the motion keeps its
+    /// duration. Nothing changes when the frame rate is not positive.
+    pub(crate) fn resample(&mut self, fps: Option<i32>) {
+        let fps = fps.unwrap_or(self.metadata.fps);
+        if fps <= 0 {
+            return;
+        }
+        let times = frame_times(self.duration(), fps);
+        for submotion in self.bone_animation.skeletal_submotion.iter_mut() {
+            submotion.bake(&times);
+        }
+        self.metadata.fps = fps;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xsm::xsm_fixtures::motion;
+
+    #[test]
+    fn last_frame_lands_on_the_duration() {
+        assert_eq!(frame_times(0.25, 10), vec![0.0, 0.1, 0.2, 0.25]);
+        assert_eq!(frame_times(0.0, 30), vec![0.0]);
+    }
+
+    #[test]
+    fn bakes_every_channel_at_the_new_rate() {
+        let original = motion(&["Bip01"], 7);
+        let mut resampled = original.clone();
+        resampled.resample(Some(60));
+        assert_eq!(resampled.metadata.fps, 60);
+        assert!((resampled.duration() - original.duration()).abs() < 1e-6);
+        let submotion = &resampled.bone_animation.skeletal_submotion[0];
+        assert_eq!(submotion.pos_key.len(), 13);
+        assert_eq!(submotion.num_scale_keys, 13);
+        assert_eq!(submotion.scale_rot_key.len(), 13);
+        for key in &submotion.pos_key {
+            let expected = original.sample(key.time).nodes.remove(0).position;
+            assert!((key.pos.x - expected.x).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn ignores_frame_rates_that_are_not_positive() {
+        let mut xsm = motion(&["Bip01"], 7);
+        xsm.resample(Some(0));
+        assert_eq!(xsm.metadata.fps, 30);
+        assert!(xsm.bone_animation.skeletal_submotion[0]
+            .scale_key
+            .is_empty());
+    }
+}
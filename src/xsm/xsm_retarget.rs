@@ -0,0 +1,197 @@
+#![allow(dead_code)]
+
+use bevy::math::{Quat, Vec3};
+
+use crate::xac::xac_enums::XacUpAxis;
+use crate::xac::xac_structs::{Xac, XacActorNode};
+use crate::xsm::xsm_root_motion::up_vector;
+use crate::xsm::xsm_structs::{Xsm, XsmQuaternion, XsmRetargetOptions, XsmSubMotion, XsmVec3d};
+
+impl XsmRetargetOptions {
+    pub(crate) fn with_bone(mut self, source_name: &str, target_name: &str) -> Self {
+        self.bone_map
+            .insert(source_name.to_string(), target_name.to_string());
+        self
+    }
+
+    fn find<'a>(&self, xac: &'a Xac, node_name: &str) -> Option<&'a XacActorNode> {
+        xac.node_hierarchy.node_data.iter().find(|node| {
+            if self.case_insensitive {
+                node.name.eq_ignore_ascii_case(node_name)
+            } else {
+                node.name == node_name
+            }
+        })
+    }
+
+    fn target_name<'a>(&'a self, source_name: &'a str) -> &'a str {
+        self.bone_map
+            .get(source_name)
+            .map(String::as_str)
+            .unwrap_or(source_name)
+    }
+}
+
+/// How a source bone's local transform maps onto the matching target bone.
+struct BoneRetarget {
+    source_rotation: Quat,
+    target_rotation: Quat,
+    source_position: Vec3,
+    target_position: Vec3,
+    position_scale: f32,
+    root_offset: Vec3,
+}
+
+impl BoneRetarget {
+    fn new(source: &XacActorNode, target: &XacActorNode, root_offset: Vec3) -> BoneRetarget {
+        let source_position = Vec3::from(&source.position);
+        let target_position = Vec3::from(&target.position);
+        let is_root = source.parent_node_id < 0;
+        let position_scale = if is_root {
+            1.0
+        } else if source_position.length() > f32::EPSILON {
+            target_position.length() / source_position.length()
+        } else {
+            1.0
+        };
+        BoneRetarget {
+            source_rotation: Quat::from(&source.rotation),
+            target_rotation: Quat::from(&target.rotation),
+            source_position,
+            target_position,
+            position_scale,
+            root_offset: if is_root { root_offset } else { Vec3::ZERO },
+        }
+    }
+
+    /// Applies the rotation the source bone has relative to its bind pose to the target
+    /// bind pose.
+    fn rotation(&self, rotation: XsmQuaternion) -> XsmQuaternion {
+        XsmQuaternion::from(
+            (self.target_rotation * self.source_rotation.inverse() * Quat::from(rotation))
+                .normalize(),
+        )
+    }
+
+    /// Keeps the target bind pose offset and scales the animated change by the bone length
+    /// ratio. The root keeps its change unscaled.
+    fn position(&self, position: &XsmVec3d) -> XsmVec3d {
+        let position = Vec3::from(position);
+        XsmVec3d::from(
+            self.target_position
+                + self.root_offset
+                + (position - self.source_position) * self.position_scale,
+        )
+    }
+}
+
+fn retarget_submotion(submotion: &mut XsmSubMotion, bone: &BoneRetarget, target: &XacActorNode) {
+    submotion.node_name = target.name.clone();
+    submotion.set_pose_rotation(bone.rotation(submotion.pose_rotation()));
+    submotion.set_bind_pose_rotation(XsmQuaternion::from(bone.target_rotation));
+    submotion.pose_pos = bone.position(&submotion.pose_pos);
+    submotion.bind_pose_pos = XsmVec3d::from(bone.target_position);
+    for key in submotion.rot_key.iter_mut() {
+        key.set_rotation(bone.rotation(key.rotation()));
+    }
+    for key in submotion.pos_key.iter_mut() {
+        key.pos = bone.position(&key.pos);
+    }
+}
+
+/// Maps a motion authored for `source` onto the skeleton of `target`.
+///
+/// Bones are matched by name through `options.bone_map`, falling back to the same name.
+/// Rotations and translations keep their offset from the bind pose, with translations
+/// scaled by bone length. The root moves by the same amount as in the source and is
+/// raised by the difference of the actors' `retarget_root_offset` along `up_axis`.
+/// Bones the target does not have are dropped.
+pub(crate) fn retarget_motion(
+    xsm: &Xsm,
+    source: &Xac,
+    target: &Xac,
+    options: &XsmRetargetOptions,
+    up_axis: XacUpAxis,
+) -> Xsm {
+    let root_offset = up_vector(up_axis)
+        * (target.metadata.retarget_root_offset - source.metadata.retarget_root_offset);
+    let mut retargeted = xsm.clone();
+    retargeted.bone_animation.skeletal_submotion = xsm
+        .bone_animation
+        .skeletal_submotion
+        .iter()
+        .filter_map(|submotion| {
+            let target_node = options.find(target, options.target_name(&submotion.node_name))?;
+            let mut submotion = submotion.clone();
+            match options.find(source, &submotion.node_name) {
+                Some(source_node) => {
+                    let bone = BoneRetarget::new(source_node, target_node, root_offset);
+                    retarget_submotion(&mut submotion, &bone, target_node);
+                }
+                None => submotion.node_name = target_node.name.clone(),
+            }
+            Some(submotion)
+        })
+        .collect();
+    retargeted.bone_animation.num_submotion =
+        retargeted.bone_animation.skeletal_submotion.len() as i32;
+    retargeted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xac::xac_fixtures::{actor, node};
+    use crate::xsm::xsm_fixtures::motion;
+
+    fn skeleton(height: f32, spine: f32, root_offset: f32) -> Xac {
+        let mut xac = actor(vec![
+            node("Bip01", -1, Vec3::new(0.0, height, 0.0), Quat::IDENTITY),
+            node("Bip01 Spine", 0, Vec3::new(0.0, spine, 0.0), Quat::IDENTITY),
+        ]);
+        xac.metadata.retarget_root_offset = root_offset;
+        xac
+    }
+
+    #[test]
+    fn maps_onto_a_skeleton_with_other_proportions() {
+        let xsm = motion(&["Bip01", "Bip01 Spine", "Bip01 Tail"], 4);
+        let source = skeleton(1.0, 0.5, 0.1);
+        let target = skeleton(2.0, 1.0, 0.3);
+        let retargeted = retarget_motion(
+            &xsm,
+            &source,
+            &target,
+            &XsmRetargetOptions::default(),
+            XacUpAxis::Y,
+        );
+        let submotions = &retargeted.bone_animation.skeletal_submotion;
+        assert_eq!(submotions.len(), 2);
+        for (root, spine) in submotions[0]
+            .pos_key
+            .iter()
+            .zip(submotions[1].pos_key.iter())
+        {
+            let time = root.time;
+            let expected_root = Vec3::new(2.0 * time, 2.2, 0.0);
+            assert!(Vec3::from(&root.pos).abs_diff_eq(expected_root, 1e-5));
+            let expected_spine = Vec3::new(4.0 * time, 2.0, 0.0);
+            assert!(Vec3::from(&spine.pos).abs_diff_eq(expected_spine, 1e-5));
+        }
+        assert_eq!(submotions[0].bind_pose_pos.y, 2.0);
+    }
+
+    #[test]
+    fn root_keeps_its_bind_delta_without_offsets() {
+        let xsm = motion(&["Bip01"], 4);
+        let retargeted = retarget_motion(
+            &xsm,
+            &skeleton(1.0, 0.5, 0.0),
+            &skeleton(2.0, 1.0, 0.0),
+            &XsmRetargetOptions::default(),
+            XacUpAxis::Y,
+        );
+        let key = &retargeted.bone_animation.skeletal_submotion[0].pos_key[3];
+        assert!(Vec3::from(&key.pos).abs_diff_eq(Vec3::new(0.2, 2.0, 0.0), 1e-5));
+    }
+}
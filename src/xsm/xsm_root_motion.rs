@@ -0,0 +1,164 @@
+#![allow(dead_code)]
+
+use bevy::math::{Quat, Vec3};
+
+use crate::xac::xac_enums::XacUpAxis;
+use crate::xsm::xsm_sample::key_segment;
+use crate::xsm::xsm_structs::{
+    Xsm, XsmQuaternion, XsmRootMotion, XsmRootMotionKey, XsmSubMotion, XsmVec3d,
+};
+
+pub(crate) fn up_vector(up_axis: XacUpAxis) -> Vec3 {
+    match up_axis {
+        XacUpAxis::Y => Vec3::Y,
+        XacUpAxis::Z => Vec3::Z,
+    }
+}
+
+/// Twist part of a swing-twist decomposition of `rotation` around `up`.
+fn yaw(rotation: Quat, up: Vec3) -> Quat {
+    let projected = up * rotation.xyz().dot(up);
+    let twist = Quat::from_xyzw(projected.x, projected.y, projected.z, rotation.w);
+    if twist.length_squared() > f32::EPSILON {
+        twist.normalize()
+    } else {
+        Quat::IDENTITY
+    }
+}
+
+impl XsmSubMotion {
+    /// Horizontal translation and yaw of this node relative to its first frame.
+    fn root_delta(&self, time: f32, start: (Vec3, Quat), up: Vec3) -> (Vec3, Quat) {
+        let position = Vec3::from(&self.sample_position(time));
+        let rotation = Quat::from(self.sample_rotation(time));
+        let horizontal = position - up * position.dot(up);
+        (
+            horizontal - start.0,
+            (yaw(rotation, up) * start.1.inverse()).normalize(),
+        )
+    }
+}
+
+impl Xsm {
+    /// Moves the horizontal translation and yaw of `node_name` into a root motion track and
+    /// leaves the node in place at its first frame. Returns `None` when the motion does not
+    /// animate that node.
+    pub(crate) fn extract_root_motion(
+        &mut self,
+        node_name: &str,
+        up_axis: XacUpAxis,
+    ) -> Option<XsmRootMotion> {
+        let up = up_vector(up_axis);
+        let submotion = self
+            .bone_animation
+            .skeletal_submotion
+            .iter_mut()
+            .find(|submotion| submotion.node_name == node_name)?;
+
+        let first_position = Vec3::from(&submotion.sample_position(0.0));
+        let start = (
+            first_position - up * first_position.dot(up),
+            yaw(Quat::from(submotion.sample_rotation(0.0)), up),
+        );
+        let mut times: Vec<f32> = submotion
+            .pos_key
+            .iter()
+            .map(|key| key.time)
+            .chain(submotion.rot_key.iter().map(|key| key.time))
+            .collect();
+        times.sort_by(f32::total_cmp);
+        times.dedup();
+        let keys: Vec<XsmRootMotionKey> = times
+            .iter()
+            .map(|&time| {
+                let (translation, rotation) = submotion.root_delta(time, start, up);
+                XsmRootMotionKey {
+                    translation: XsmVec3d::from(translation),
+                    rotation: XsmQuaternion::from(rotation),
+                    time,
+                }
+            })
+            .collect();
+
+        let deltas: Vec<(Vec3, Quat)> = submotion
+            .pos_key
+            .iter()
+            .map(|key| submotion.root_delta(key.time, start, up))
+            .collect();
+        for (key, (translation, _)) in submotion.pos_key.iter_mut().zip(deltas) {
+            key.pos = XsmVec3d::from(Vec3::from(&key.pos) - translation);
+        }
+        let deltas: Vec<(Vec3, Quat)> = submotion
+            .rot_key
+            .iter()
+            .map(|key| submotion.root_delta(key.time, start, up))
+            .collect();
+        for (key, (_, rotation)) in submotion.rot_key.iter_mut().zip(deltas) {
+            let in_place = rotation.inverse() * Quat::from(key.rotation());
+            key.set_rotation(XsmQuaternion::from(in_place.normalize()));
+        }
+
+        Some(XsmRootMotion {
+            node_name: node_name.to_string(),
+            keys,
+        })
+    }
+}
+
+impl XsmRootMotion {
+    /// Translation and yaw accumulated since the start of the motion at `time`.
+    pub(crate) fn sample(&self, time: f32) -> XsmRootMotionKey {
+        if self.keys.is_empty() {
+            return XsmRootMotionKey {
+                translation: XsmVec3d::from(Vec3::ZERO),
+                rotation: XsmQuaternion::IDENTITY,
+                time,
+            };
+        }
+        let times: Vec<f32> = self.keys.iter().map(|key| key.time).collect();
+        let (previous, next, factor) = key_segment(&times, time);
+        let (previous, next) = (&self.keys[previous], &self.keys[next]);
+        XsmRootMotionKey {
+            translation: XsmVec3d::from(
+                Vec3::from(&previous.translation).lerp(Vec3::from(&next.translation), factor),
+            ),
+            rotation: XsmQuaternion::from(
+                Quat::from(previous.rotation)
+                    .slerp(Quat::from(next.rotation), factor)
+                    .normalize(),
+            ),
+            time,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xsm::xsm_fixtures::motion;
+
+    #[test]
+    fn root_motion_and_in_place_clip_rebuild_the_root_track() {
+        let original = motion(&["Bip01"], 10);
+        let mut in_place = original.clone();
+        let root_motion = in_place.extract_root_motion("Bip01", XacUpAxis::Y).unwrap();
+        let original = &original.bone_animation.skeletal_submotion[0];
+        let submotion = &in_place.bone_animation.skeletal_submotion[0];
+        for (key, original) in submotion.pos_key.iter().zip(original.pos_key.iter()) {
+            let delta = root_motion.sample(key.time);
+            assert!(Vec3::from(&key.pos).abs_diff_eq(Vec3::new(0.0, 1.0, 0.0), 1e-5));
+            let rebuilt = Vec3::from(&key.pos) + Vec3::from(&delta.translation);
+            assert!(rebuilt.abs_diff_eq(Vec3::from(&original.pos), 1e-5));
+        }
+        for (key, original) in submotion.rot_key.iter().zip(original.rot_key.iter()) {
+            let delta = root_motion.sample(key.time);
+            assert!(Quat::from(key.rotation()).angle_between(Quat::IDENTITY) < 1e-3);
+            let rebuilt = Quat::from(delta.rotation) * Quat::from(key.rotation());
+            assert!(rebuilt.angle_between(Quat::from(original.rotation())) < 1e-3);
+        }
+        assert!(root_motion.sample(0.0).translation.x.abs() < 1e-6);
+        assert!(in_place
+            .extract_root_motion("Bip01 Head", XacUpAxis::Y)
+            .is_none());
+    }
+}
@@ -0,0 +1,186 @@
+#![allow(dead_code)]
+
+use bevy::math::{Quat, Vec3};
+
+use crate::xsm::xsm_enums::XsmWrapMode;
+use crate::xsm::xsm_structs::{Xsm, XsmNodePose, XsmPose, XsmQuaternion, XsmSubMotion, XsmVec3d};
+
+/// Finds the keys around `time` in a list sorted by time and the blend factor between them.
+/// Times outside the key range clamp to the first or last key.
+pub(crate) fn key_segment(times: &[f32], time: f32) -> (usize, usize, f32) {
+    let next = times.partition_point(|&key_time| key_time <= time);
+    if next == 0 {
+        return (0, 0, 0.0);
+    }
+    if next == times.len() {
+        return (next - 1, next - 1, 0.0);
+    }
+    let previous = next - 1;
+    let span = times[next] - times[previous];
+    let factor = if span > 0.0 {
+        (time - times[previous]) / span
+    } else {
+        0.0
+    };
+    (previous, next, factor)
+}
+
+fn sample_vec3(times: &[f32], values: &[Vec3], time: f32) -> Vec3 {
+    let (previous, next, factor) = key_segment(times, time);
+    values[previous].lerp(values[next], factor)
+}
+
+fn sample_quat(times: &[f32], values: &[Quat], time: f32) -> Quat {
+    let (previous, next, factor) = key_segment(times, time);
+    values[previous].slerp(values[next], factor).normalize()
+}
+
+impl XsmSubMotion {
+    pub(crate) fn sample_position(&self, time: f32) -> XsmVec3d {
+        if self.pos_key.is_empty() {
+            return self.pose_pos;
+        }
+        let times: Vec<f32> = self.pos_key.iter().map(|key| key.time).collect();
+        let values: Vec<Vec3> = self
+            .pos_key
+            .iter()
+            .map(|key| Vec3::from(&key.pos))
+            .collect();
+        XsmVec3d::from(sample_vec3(&times, &values, time))
+    }
+
+    pub(crate) fn sample_rotation(&self, time: f32) -> XsmQuaternion {
+        if self.rot_key.is_empty() {
+            return self.pose_rotation();
+        }
+        let times: Vec<f32> = self.rot_key.iter().map(|key| key.time).collect();
+        let values: Vec<Quat> = self
+            .rot_key
+            .iter()
+            .map(|key| Quat::from(key.rotation()))
+            .collect();
+        XsmQuaternion::from(sample_quat(&times, &values, time))
+    }
+
+    pub(crate) fn sample_scale(&self, time: f32) -> XsmVec3d {
+        if self.scale_key.is_empty() {
+            return self.pose_scale;
+        }
+        let times: Vec<f32> = self.scale_key.iter().map(|key| key.time).collect();
+        let values: Vec<Vec3> = self
+            .scale_key
+            .iter()
+            .map(|key| Vec3::from(&key.scale))
+            .collect();
+        XsmVec3d::from(sample_vec3(&times, &values, time))
+    }
+
+    pub(crate) fn sample_scale_rotation(&self, time: f32) -> XsmQuaternion {
+        if self.scale_rot_key.is_empty() {
+            return self.pose_scale_rotation();
+        }
+        let times: Vec<f32> = self.scale_rot_key.iter().map(|key| key.time).collect();
+        let values: Vec<Quat> = self
+            .scale_rot_key
+            .iter()
+            .map(|key| Quat::from(key.rotation()))
+            .collect();
+        XsmQuaternion::from(sample_quat(&times, &values, time))
+    }
+
+    pub(crate) fn sample(&self, time: f32) -> XsmNodePose {
+        XsmNodePose {
+            node_name: self.node_name.clone(),
+            position: self.sample_position(time),
+            rotation: self.sample_rotation(time),
+            scale: self.sample_scale(time),
+            scale_rotation: self.sample_scale_rotation(time),
+        }
+    }
+
+    /// Time of the last key over all channels.
+    pub(crate) fn duration(&self) -> f32 {
+        let pos = self.pos_key.iter().map(|key| key.time);
+        let rot = self.rot_key.iter().map(|key| key.time);
+        let scale = self.scale_key.iter().map(|key| key.time);
+        let scale_rot = self.scale_rot_key.iter().map(|key| key.time);
+        pos.chain(rot)
+            .chain(scale)
+            .chain(scale_rot)
+            .fold(0.0, f32::max)
+    }
+}
+
+impl Xsm {
+    /// Time of the last key in the motion.
+    pub(crate) fn duration(&self) -> f32 {
+        self.bone_animation
+            .skeletal_submotion
+            .iter()
+            .map(|submotion| submotion.duration())
+            .fold(0.0, f32::max)
+    }
+
+    /// Evaluates every submotion at `time`, holding the first and last keys outside the key range.
+    pub(crate) fn sample(&self, time: f32) -> XsmPose {
+        self.sample_with_mode(time, XsmWrapMode::Clamp)
+    }
+
+    pub(crate) fn sample_with_mode(&self, time: f32, mode: XsmWrapMode) -> XsmPose {
+        let duration = self.duration();
+        let time = match mode {
+            XsmWrapMode::Loop if duration > 0.0 => time.rem_euclid(duration),
+            _ => time,
+        };
+        XsmPose {
+            nodes: self
+                .bone_animation
+                .skeletal_submotion
+                .iter()
+                .map(|submotion| submotion.sample(time))
+                .collect(),
+        }
+    }
+}
+
+impl XsmPose {
+    pub(crate) fn node(&self, node_name: &str) -> Option<&XsmNodePose> {
+        self.nodes.iter().find(|node| node.node_name == node_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xsm::xsm_fixtures::motion;
+
+    #[test]
+    fn key_segment_clamps_and_interpolates() {
+        let times = [0.0, 1.0, 3.0];
+        assert_eq!(key_segment(&times, -1.0), (0, 0, 0.0));
+        assert_eq!(key_segment(&times, 2.0), (1, 2, 0.5));
+        assert_eq!(key_segment(&times, 5.0), (2, 2, 0.0));
+    }
+
+    #[test]
+    fn samples_between_keys() {
+        let xsm = motion(&["Bip01"], 4);
+        let time = 1.5 / 30.0;
+        let node = xsm.sample(time).nodes.remove(0);
+        assert!((node.position.x - time * 2.0).abs() < 1e-5);
+        let expected = Quat::from_rotation_y(time);
+        assert!(Quat::from(node.rotation).abs_diff_eq(expected, 1e-3));
+        assert_eq!(node.scale.x, 1.0);
+    }
+
+    #[test]
+    fn clamps_or_loops_outside_the_key_range() {
+        let xsm = motion(&["Bip01"], 4);
+        assert!((xsm.duration() - 0.1).abs() < 1e-6);
+        let clamped = xsm.sample(0.15);
+        assert!((clamped.nodes[0].position.x - 0.2).abs() < 1e-5);
+        let looped = xsm.sample_with_mode(0.15, XsmWrapMode::Loop);
+        assert!((looped.nodes[0].position.x - 0.1).abs() < 1e-5);
+        assert!(looped.node("Bip01").is_some());
+    }
+}
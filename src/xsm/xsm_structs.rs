@@ -1,14 +1,20 @@
 #![allow(dead_code)]
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
-#[derive(Debug,Serialize,Deserialize)]
+
+use crate::xsm::xsm_enums::{XsmChannel, XsmKeyProblem, XsmMirrorPlane};
+use crate::xsmtime::xsmtime_structs::XsmTimeEvent;
+
+#[derive(Debug,Clone,Copy,Serialize,Deserialize)]
 pub(crate) struct XsmVec3d {
     pub(crate) x: f32,
     pub(crate) y: f32,
     pub(crate) z: f32,
 }
 
-#[derive(Debug,Serialize,Deserialize)]
+#[derive(Debug,Clone,Copy,Serialize,Deserialize)]
 pub(crate) struct XsmQuaternion16 {
     pub(crate) x: i16,
     pub(crate) y: i16,
@@ -16,14 +22,30 @@ pub(crate) struct XsmQuaternion16 {
     pub(crate) w: i16,
 }
 
-#[derive(Debug,Serialize,Deserialize)]
+#[derive(Debug,Clone,Copy,PartialEq,Serialize,Deserialize)]
+pub(crate) struct XsmQuaternion {
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) z: f32,
+    pub(crate) w: f32,
+}
+
+#[derive(Debug,Clone,Copy,Serialize,Deserialize)]
+pub(crate) enum XsmRotation {
+    Compressed(XsmQuaternion16),
+    Uncompressed(XsmQuaternion),
+}
+
+#[derive(Debug,Clone,Serialize,Deserialize)]
 pub struct Xsm {
     pub(crate) header: XsmHeader,
     pub(crate) metadata: XsmMetadata,
     pub(crate) bone_animation: XsmBoneAnimation,
+    pub(crate) chunks: Vec<XsmChunk>,
+    pub(crate) events: Vec<XsmTimeEvent>,
 }
 
-#[derive(Debug,Serialize,Deserialize)]
+#[derive(Debug,Clone,Serialize,Deserialize)]
 pub(crate) struct XsmHeader {
     pub(crate) magic: String,
     pub(crate) major_version: u8,
@@ -32,14 +54,15 @@ pub(crate) struct XsmHeader {
 }
 
 #[allow(dead_code)]
-#[derive(Debug,Serialize,Deserialize)]
+#[derive(Debug,Clone,Serialize,Deserialize)]
 pub(crate) struct XsmChunk {
     pub(crate) chunk_type: i32,
     pub(crate) length: i32,
     pub(crate) version: i32,
+    pub(crate) data: Vec<u8>,
 }
 
-#[derive(Debug,Serialize,Deserialize)]
+#[derive(Debug,Clone,Serialize,Deserialize)]
 pub(crate) struct XsmMetadata {
     pub(crate) unused: f32,
     pub(crate) max_acceptable_error: f32,
@@ -52,19 +75,19 @@ pub(crate) struct XsmMetadata {
     pub(crate) motion_name: String,
 }
 
-#[derive(Debug,Serialize,Deserialize)]
+#[derive(Debug,Clone,Serialize,Deserialize)]
 pub(crate) struct XsmBoneAnimation {
     pub(crate) num_submotion: i32,
     pub(crate) skeletal_submotion: Vec<XsmSubMotion>,
 }
 
 #[allow(dead_code)]
-#[derive(Debug,Serialize,Deserialize)]
+#[derive(Debug,Clone,Serialize,Deserialize)]
 pub(crate) struct XsmSubMotion {
-    pub(crate) pose_rot: XsmQuaternion16,
-    pub(crate) bind_pose_rot: XsmQuaternion16,
-    pub(crate) pose_scale_rot: XsmQuaternion16,
-    pub(crate) bind_pose_scale_rot: XsmQuaternion16,
+    pub(crate) pose_rot: XsmRotation,
+    pub(crate) bind_pose_rot: XsmRotation,
+    pub(crate) pose_scale_rot: XsmRotation,
+    pub(crate) bind_pose_scale_rot: XsmRotation,
     pub(crate) pose_pos: XsmVec3d,
     pub(crate) pose_scale: XsmVec3d,
     pub(crate) bind_pose_pos: XsmVec3d,
@@ -82,29 +105,162 @@ pub(crate) struct XsmSubMotion {
 }
 
 #[allow(dead_code)]
-#[derive(Debug,Serialize,Deserialize)]
+#[derive(Debug,Clone,Serialize,Deserialize)]
 pub(crate) struct XsmPosKey {
     pub(crate) pos: XsmVec3d,
     pub(crate) time: f32,
 }
 
 #[allow(dead_code)]
-#[derive(Debug,Serialize,Deserialize)]
+#[derive(Debug,Clone,Serialize,Deserialize)]
 pub(crate) struct XsmRotKey {
-    pub(crate) rot: XsmQuaternion16,
+    pub(crate) rot: XsmRotation,
     pub(crate) time: f32,
 }
 
 #[allow(dead_code)]
-#[derive(Debug,Serialize,Deserialize)]
+#[derive(Debug,Clone,Serialize,Deserialize)]
 pub(crate) struct XsmScaleKey {
     pub(crate) scale: XsmVec3d,
     pub(crate) time: f32,
 }
 
 #[allow(dead_code)]
-#[derive(Debug,Serialize,Deserialize)]
+#[derive(Debug,Clone,Serialize,Deserialize)]
 pub(crate) struct XsmScaleRotKey {
-    pub(crate) rot: XsmQuaternion16,
+    pub(crate) rot: XsmRotation,
     pub(crate) time: f32,
 }
+
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub(crate) struct XsmPose {
+    pub(crate) nodes: Vec<XsmNodePose>,
+}
+
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub(crate) struct XsmNodePose {
+    pub(crate) node_name: String,
+    pub(crate) position: XsmVec3d,
+    pub(crate) rotation: XsmQuaternion,
+    pub(crate) scale: XsmVec3d,
+    pub(crate) scale_rotation: XsmQuaternion,
+}
+
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub(crate) struct XsmTrackReduction {
+    pub(crate) node_name: String,
+    pub(crate) pos_keys_removed: usize,
+    pub(crate) rot_keys_removed: usize,
+    pub(crate) scale_keys_removed: usize,
+    pub(crate) scale_rot_keys_removed: usize,
+}
+
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub(crate) struct XsmReductionReport {
+    pub(crate) tracks: Vec<XsmTrackReduction>,
+}
+
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub(crate) struct XsmRootMotion {
+    pub(crate) node_name: String,
+    pub(crate) keys: Vec<XsmRootMotionKey>,
+}
+
+#[derive(Debug,Clone,Copy,Serialize,Deserialize)]
+pub(crate) struct XsmRootMotionKey {
+    pub(crate) translation: XsmVec3d,
+    pub(crate) rotation: XsmQuaternion,
+    pub(crate) time: f32,
+}
+
+#[derive(Debug,Clone,Default,Serialize,Deserialize)]
+pub(crate) struct XsmBoneMask {
+    pub(crate) weights: HashMap<String, f32>,
+}
+
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub(crate) struct XsmSummary {
+    pub(crate) motion_name: String,
+    pub(crate) source_app: String,
+    pub(crate) exporter_version: String,
+    pub(crate) duration: f32,
+    pub(crate) fps: i32,
+    pub(crate) bone_count: usize,
+    pub(crate) total_keys: usize,
+    pub(crate) tracks: Vec<XsmTrackSummary>,
+}
+
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub(crate) struct XsmTrackSummary {
+    pub(crate) node_name: String,
+    pub(crate) pos_keys: usize,
+    pub(crate) rot_keys: usize,
+    pub(crate) scale_keys: usize,
+    pub(crate) scale_rot_keys: usize,
+    pub(crate) constant_pos: bool,
+    pub(crate) constant_rot: bool,
+    pub(crate) constant_scale: bool,
+    pub(crate) constant_scale_rot: bool,
+}
+
+#[derive(Debug,Clone,Default,Serialize,Deserialize)]
+pub(crate) struct XsmBindOptions {
+    pub(crate) case_insensitive: bool,
+    pub(crate) aliases: HashMap<String, String>,
+}
+
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub(crate) struct XsmBinding {
+    pub(crate) tracks: Vec<XsmBoundTrack>,
+    pub(crate) unmatched_motion_bones: Vec<String>,
+    pub(crate) unmatched_actor_bones: Vec<String>,
+}
+
+#[derive(Debug,Clone,Copy,Serialize,Deserialize)]
+pub(crate) struct XsmBoundTrack {
+    pub(crate) submotion_index: usize,
+    pub(crate) node_index: usize,
+}
+
+#[derive(Debug,Clone,Default,Serialize,Deserialize)]
+pub(crate) struct XsmRetargetOptions {
+    pub(crate) bone_map: HashMap<String, String>,
+    pub(crate) case_insensitive: bool,
+}
+
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub(crate) struct XsmMirrorOptions {
+    pub(crate) left_pattern: String,
+    pub(crate) right_pattern: String,
+    pub(crate) plane: XsmMirrorPlane,
+}
+
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub(crate) struct XsmValidateOptions {
+    pub(crate) bind: XsmBindOptions,
+    pub(crate) position_tolerance: f32,
+    pub(crate) rotation_tolerance: f32,
+}
+
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub(crate) struct XsmValidationReport {
+    pub(crate) missing_bones: Vec<String>,
+    pub(crate) uncovered_bones: Vec<String>,
+    pub(crate) bind_pose_mismatches: Vec<XsmBindPoseMismatch>,
+    pub(crate) broken_keys: Vec<XsmBrokenKey>,
+}
+
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub(crate) struct XsmBindPoseMismatch {
+    pub(crate) node_name: String,
+    pub(crate) position_error: f32,
+    pub(crate) rotation_error: f32,
+}
+
+#[derive(Debug,Clone,Serialize,Deserialize)]
+pub(crate) struct XsmBrokenKey {
+    pub(crate) node_name: String,
+    pub(crate) channel: XsmChannel,
+    pub(crate) key_index: usize,
+    pub(crate) problem: XsmKeyProblem,
+}
@@ -0,0 +1,143 @@
+#![allow(dead_code)]
+
+use std::fmt;
+
+use bevy::math::{Quat, Vec3};
+
+use crate::xsm::xsm_structs::{Xsm, XsmSubMotion, XsmSummary, XsmTrackSummary};
+
+/// Tolerance below which two keys count as the same value.
+const CONSTANT_EPSILON: f32 = 1e-5;
+
+fn constant_vec3(values: impl Iterator<Item = Vec3>) -> bool {
+    let values: Vec<Vec3> = values.collect();
+    values
+        .iter()
+        .all(|value| value.abs_diff_eq(values[0], CONSTANT_EPSILON))
+}
+
+fn constant_quat(values: impl Iterator<Item = Quat>) -> bool {
+    let values: Vec<Quat> = values.collect();
+    values
+        .iter()
+        .all(|value| value.angle_between(values[0]) <= CONSTANT_EPSILON)
+}
+
+impl XsmSubMotion {
+    /// Key counts per channel. A channel is constant when all of its keys hold the same
+    /// value, which includes channels without keys.
+    pub(crate) fn summary(&self) -> XsmTrackSummary {
+        XsmTrackSummary {
+            node_name: self.node_name.clone(),
+            pos_keys: self.pos_key.len(),
+            rot_keys: self.rot_key.len(),
+            scale_keys: self.scale_key.len(),
+            scale_rot_keys: self.scale_rot_key.len(),
+            constant_pos: constant_vec3(self.pos_key.iter().map(|key| Vec3::from(&key.pos))),
+            constant_rot: constant_quat(self.rot_key.iter().map(|key| Quat::from(key.rotation()))),
+            constant_scale: constant_vec3(self.scale_key.iter().map(|key| Vec3::from(&key.scale))),
+            constant_scale_rot: constant_quat(
+                self.scale_rot_key
+                    .iter()
+                    .map(|key| Quat::from(key.rotation())),
+            ),
+        }
+    }
+}
+
+impl Xsm {
+    pub(crate) fn summary(&self) -> XsmSummary {
+        let tracks: Vec<XsmTrackSummary> = self
+            .bone_animation
+            .skeletal_submotion
+            .iter()
+            .map(|submotion| submotion.summary())
+            .collect();
+        XsmSummary {
+            motion_name: self.metadata.motion_name.clone(),
+            source_app: self.metadata.source_app.clone(),
+            exporter_version: format!(
+                "{}.{}",
+                self.metadata.exporter_major_version, self.metadata.exporter_minor_version
+            ),
+            duration: self.duration(),
+            fps: self.metadata.fps,
+            bone_count: tracks.len(),
+            total_keys: tracks
+                .iter()
+                .map(|track| {
+                    track.pos_keys + track.rot_keys + track.scale_keys + track.scale_rot_keys
+                })
+                .sum(),
+            tracks,
+        }
+    }
+}
+
+impl XsmTrackSummary {
+    fn constant_channels(&self) -> String {
+        let channels = [
+            (self.constant_pos, "pos"),
+            (self.constant_rot, "rot"),
+            (self.constant_scale, "scale"),
+            (self.constant_scale_rot, "scale_rot"),
+        ];
+        let constant: Vec<&str> = channels
+            .iter()
+            .filter(|(constant, _)| *constant)
+            .map(|(_, name)| *name)
+            .collect();
+        if constant.is_empty() {
+            "-".to_string()
+        } else {
+            constant.join(",")
+        }
+    }
+}
+
+impl XsmSummary {
+    pub(crate) fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl fmt::Display for XsmSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Motion:   {}", self.motion_name)?;
+        writeln!(
+            f,
+            "Source:   {} (exporter {})",
+            self.source_app, self.exporter_version
+        )?;
+        writeln!(
+            f,
+            "Duration: {:.3}s at {} fps, {} bones, {} keys",
+            self.duration, self.fps, self.bone_count, self.total_keys
+        )?;
+        let name_width = self
+            .tracks
+            .iter()
+            .map(|track| track.node_name.len())
+            .chain(std::iter::once(4))
+            .max()
+            .unwrap_or(4);
+        writeln!(
+            f,
+            "{:<name_width$} {:>6} {:>6} {:>6} {:>9}  Constant",
+            "Node", "Pos", "Rot", "Scale", "ScaleRot"
+        )?;
+        for track in self.tracks.iter() {
+            writeln!(
+                f,
+                "{:<name_width$} {:>6} {:>6} {:>6} {:>9}  {}",
+                track.node_name,
+                track.pos_keys,
+                track.rot_keys,
+                track.scale_keys,
+                track.scale_rot_keys,
+                track.constant_channels()
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,196 @@
+#![allow(dead_code)]
+
+use bevy::math::{Quat, Vec3};
+
+use crate::xac::xac_structs::Xac;
+use crate::xsm::xsm_enums::{XsmChannel, XsmKeyProblem};
+use crate::xsm::xsm_structs::{
+    Xsm, XsmBindOptions, XsmBindPoseMismatch, XsmBrokenKey, XsmSubMotion, XsmValidateOptions,
+    XsmValidationReport,
+};
+
+impl Default for XsmValidateOptions {
+    fn default() -> Self {
+        XsmValidateOptions {
+            bind: XsmBindOptions::default(),
+            position_tolerance: 0.001,
+            rotation_tolerance: 0.01,
+        }
+    }
+}
+
+impl XsmValidationReport {
+    pub(crate) fn is_valid(&self) -> bool {
+        self.missing_bones.is_empty()
+            && self.uncovered_bones.is_empty()
+            && self.bind_pose_mismatches.is_empty()
+            && self.broken_keys.is_empty()
+    }
+}
+
+/// Flags keys whose values or times are not finite and keys earlier than the key before.
+fn check_channel(
+    broken: &mut Vec<XsmBrokenKey>,
+    node_name: &str,
+    channel: XsmChannel,
+    keys: impl Iterator<Item = (f32, bool)>,
+) {
+    let mut previous_time = f32::NEG_INFINITY;
+    for (key_index, (time, finite)) in keys.enumerate() {
+        let problem = if !finite || !time.is_finite() {
+            Some(XsmKeyProblem::NotFinite)
+        } else if time < previous_time {
+            Some(XsmKeyProblem::TimeGoesBackwards)
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            broken.push(XsmBrokenKey {
+                node_name: node_name.to_string(),
+                channel,
+                key_index,
+                problem,
+            });
+        }
+        if time.is_finite() {
+            previous_time = previous_time.max(time);
+        }
+    }
+}
+
+impl XsmSubMotion {
+    pub(crate) fn broken_keys(&self) -> Vec<XsmBrokenKey> {
+        let mut broken = vec![];
+        let name = &self.node_name;
+        check_channel(
+            &mut broken,
+            name,
+            XsmChannel::Position,
+            self.pos_key
+                .iter()
+                .map(|key| (key.time, Vec3::from(&key.pos).is_finite())),
+        );
+        check_channel(
+            &mut broken,
+            name,
+            XsmChannel::Rotation,
+            self.rot_key
+                .iter()
+                .map(|key| (key.time, Quat::from(key.rotation()).is_finite())),
+        );
+        check_channel(
+            &mut broken,
+            name,
+            XsmChannel::Scale,
+            self.scale_key
+                .iter()
+                .map(|key| (key.time, Vec3::from(&key.scale).is_finite())),
+        );
+        check_channel(
+            &mut broken,
+            name,
+            XsmChannel::ScaleRotation,
+            self.scale_rot_key
+                .iter()
+                .map(|key| (key.time, Quat::from(key.rotation()).is_finite())),
+        );
+        broken
+    }
+}
+
+impl Xsm {
+    /// Checks whether the motion fits `xac`: bones are matched as in `Xsm::bind`, the bind
+    /// pose stored with each track is compared with the node's, and every key is checked
+    /// for broken values and times.
+    pub(crate) fn validate(&self, xac: &Xac, options: &XsmValidateOptions) -> XsmValidationReport {
+        let binding = self.bind(xac, &options.bind);
+        let bind_pose_mismatches = binding
+            .tracks
+            .iter()
+            .filter_map(|track| {
+                let submotion = &self.bone_animation.skeletal_submotion[track.submotion_index];
+                let node = &xac.node_hierarchy.node_data[track.node_index];
+                let position_error =
+                    Vec3::from(&submotion.bind_pose_pos).distance(Vec3::from(&node.position));
+                let rotation_error = Quat::from(submotion.bind_pose_rotation())
+                    .angle_between(Quat::from(&node.rotation));
+                (position_error > options.position_tolerance
+                    || rotation_error > options.rotation_tolerance)
+                    .then(|| XsmBindPoseMismatch {
+                        node_name: submotion.node_name.clone(),
+                        position_error,
+                        rotation_error,
+                    })
+            })
+            .collect();
+        XsmValidationReport {
+            missing_bones: binding.unmatched_motion_bones,
+            uncovered_bones: binding.unmatched_actor_bones,
+            bind_pose_mismatches,
+            broken_keys: self
+                .bone_animation
+                .skeletal_submotion
+                .iter()
+                .flat_map(|submotion| submotion.broken_keys())
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xac::xac_fixtures::{actor, node};
+    use crate::xsm::xsm_fixtures::motion;
+
+    /// The fixture tracks are bound at (0, 1, 0), which only the spine matches.
+    fn skeleton() -> Xac {
+        actor(vec![
+            node("Bip01", -1, Vec3::ZERO, Quat::IDENTITY),
+            node("Bip01 Spine", 0, Vec3::Y, Quat::IDENTITY),
+        ])
+    }
+
+    #[test]
+    fn matching_motion_is_valid() {
+        let xsm = motion(&["Bip01 Spine"], 4);
+        let xac = actor(vec![node("Bip01 Spine", -1, Vec3::Y, Quat::IDENTITY)]);
+        assert!(xsm
+            .validate(&xac, &XsmValidateOptions::default())
+            .is_valid());
+    }
+
+    #[test]
+    fn reports_bind_pose_mismatches_and_bone_coverage() {
+        let xsm = motion(&["Bip01", "Bip01 Spine", "Tail"], 4);
+        let report = xsm.validate(&skeleton(), &XsmValidateOptions::default());
+        assert!(!report.is_valid());
+        assert_eq!(report.missing_bones, vec!["Tail"]);
+        assert!(report.uncovered_bones.is_empty());
+        assert_eq!(report.bind_pose_mismatches.len(), 1);
+        let mismatch = &report.bind_pose_mismatches[0];
+        assert_eq!(mismatch.node_name, "Bip01");
+        assert!((mismatch.position_error - 1.0).abs() < 1e-6);
+        assert!(mismatch.rotation_error < 1e-3);
+    }
+
+    #[test]
+    fn flags_nan_and_backwards_keys() {
+        let mut xsm = motion(&["Bip01 Spine"], 4);
+        let submotion = &mut xsm.bone_animation.skeletal_submotion[0];
+        submotion.pos_key[1].pos.x = f32::NAN;
+        submotion.rot_key[3].time = 0.0;
+        let broken = submotion.broken_keys();
+        assert_eq!(broken.len(), 2);
+        assert_eq!(broken[0].channel, XsmChannel::Position);
+        assert_eq!(broken[0].key_index, 1);
+        assert_eq!(broken[0].problem, XsmKeyProblem::NotFinite);
+        assert_eq!(broken[1].channel, XsmChannel::Rotation);
+        assert_eq!(broken[1].key_index, 3);
+        assert_eq!(broken[1].problem, XsmKeyProblem::TimeGoesBackwards);
+        let xac = actor(vec![node("Bip01 Spine", -1, Vec3::Y, Quat::IDENTITY)]);
+        let report = xsm.validate(&xac, &XsmValidateOptions::default());
+        assert_eq!(report.broken_keys.len(), 2);
+        assert!(!report.is_valid());
+    }
+}
@@ -0,0 +1,365 @@
+#![allow(dead_code)]
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use byteorder::{BigEndian, ByteOrder, LittleEndian, WriteBytesExt};
+
+use crate::xsm::xsm_enums::XsmChunkType::{XsmBoneAnimationId, XsmMetadataId, XsmSubMotionId};
+use crate::xsm::xsm_enums::XsmError;
+use crate::xsm::xsm_parser::is_known_submotion_chunk;
+use crate::xsm::xsm_structs::{
+    Xsm, XsmChunk, XsmQuaternion16, XsmRotation, XsmSubMotion, XsmVec3d,
+};
+
+pub fn xsmwrite(xsm: &Xsm, path: &str) -> Result<(), XsmError> {
+    let xsm_file = File::create(path)?;
+    let mut writer = BufWriter::new(xsm_file);
+    xsm.to_writer(&mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+impl Xsm {
+    /// Serializes the motion using the chunk layout it was read with. Motions that were
+    /// not read from a file get a version 1 metadata chunk and a bone animation chunk.
+    /// Submotion chunks always follow the current submotions.
+    pub fn to_writer<W: Write>(&self, mut writer: W) -> Result<(), XsmError> {
+        writer.write_all(self.header.magic.as_bytes())?;
+        writer.write_u8(self.header.major_version)?;
+        writer.write_u8(self.header.minor_version)?;
+        writer.write_u8(self.header.big_endian as u8)?;
+        writer.write_u8(0)?; // Padding
+        if self.header.big_endian {
+            write_chunks::<BigEndian, W>(&mut writer, self)
+        } else {
+            write_chunks::<LittleEndian, W>(&mut writer, self)
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, XsmError> {
+        let mut bytes = vec![];
+        self.to_writer(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+fn default_chunks() -> Vec<XsmChunk> {
+    vec![
+        XsmChunk {
+            chunk_type: XsmMetadataId as i32,
+            length: 0,
+            version: 1,
+            data: vec![],
+        },
+        XsmChunk {
+            chunk_type: XsmBoneAnimationId as i32,
+            length: 0,
+            version: 1,
+            data: vec![],
+        },
+    ]
+}
+
+/// Chunks holding the current submotions: one submotion chunk for each submotion stored
+/// with float quaternions and one bone animation chunk for each run of submotions stored
+/// with 16 bit quaternions. Version and trailing data are taken from the chunk of the same
+/// type and position in `recorded`, so an unmodified motion keeps its layout.
+fn submotion_chunks<'a>(
+    xsm: &'a Xsm,
+    recorded: &[XsmChunk],
+) -> Vec<(XsmChunk, Vec<&'a XsmSubMotion>)> {
+    let mut groups: Vec<(i32, Vec<&XsmSubMotion>)> = vec![];
+    for submotion in xsm.bone_animation.skeletal_submotion.iter() {
+        if !matches!(submotion.pose_rot, XsmRotation::Compressed(_)) {
+            groups.push((XsmSubMotionId as i32, vec![submotion]));
+            continue;
+        }
+        match groups.last_mut() {
+            Some((chunk_type, members)) if *chunk_type == XsmBoneAnimationId as i32 => {
+                members.push(submotion)
+            }
+            _ => groups.push((XsmBoneAnimationId as i32, vec![submotion])),
+        }
+    }
+    let mut used = [0, 0];
+    groups
+        .into_iter()
+        .map(|(chunk_type, members)| {
+            let slot = &mut used[(chunk_type == XsmBoneAnimationId as i32) as usize];
+            let template = recorded
+                .iter()
+                .filter(|chunk| is_submotion_chunk(chunk) && chunk.chunk_type == chunk_type)
+                .nth(*slot);
+            *slot += 1;
+            let chunk = XsmChunk {
+                chunk_type,
+                length: 0,
+                version: template.map_or(1, |chunk| chunk.version),
+                data: template.map_or(vec![], |chunk| chunk.data.clone()),
+            };
+            (chunk, members)
+        })
+        .collect()
+}
+
+/// Submotion chunks with a version the parser skipped are written back untouched.
+fn is_submotion_chunk(chunk: &XsmChunk) -> bool {
+    is_known_submotion_chunk(chunk) || chunk.chunk_type == XsmBoneAnimationId as i32
+}
+
+/// Writes the metadata and unknown chunks in their recorded order. The submotion chunks
+/// are rebuilt from the submotions and written where the first recorded one was, or at
+/// the end when there was none, so adding or removing bones cannot drop data or leave
+/// empty chunks behind.
+fn write_chunks<E: ByteOrder, W: Write>(writer: &mut W, xsm: &Xsm) -> Result<(), XsmError> {
+    let recorded = if xsm.chunks.is_empty() {
+        default_chunks()
+    } else {
+        xsm.chunks.clone()
+    };
+    let mut layout: Vec<(XsmChunk, Vec<&XsmSubMotion>)> = vec![];
+    let mut submotions = Some(submotion_chunks(xsm, &recorded));
+    for chunk in recorded.iter() {
+        if !is_submotion_chunk(chunk) {
+            layout.push((chunk.clone(), vec![]));
+        } else if let Some(chunks) = submotions.take() {
+            layout.extend(chunks);
+        }
+    }
+    layout.extend(submotions.into_iter().flatten());
+
+    for (chunk, members) in layout.iter() {
+        let mut body: Vec<u8> = vec![];
+        if chunk.chunk_type == XsmMetadataId as i32 {
+            write_metadata::<E>(&mut body, xsm)?;
+        }
+        if is_known_submotion_chunk(chunk) {
+            for submotion in members.iter() {
+                write_submotion::<E>(&mut body, submotion, false)?;
+            }
+        }
+        if chunk.chunk_type == XsmBoneAnimationId as i32 {
+            body.write_i32::<E>(members.len() as i32)?;
+            for submotion in members.iter() {
+                write_submotion::<E>(&mut body, submotion, true)?;
+            }
+        }
+        body.extend_from_slice(&chunk.data);
+        writer.write_i32::<E>(chunk.chunk_type)?;
+        writer.write_i32::<E>(body.len() as i32)?;
+        writer.write_i32::<E>(chunk.version)?;
+        writer.write_all(&body)?;
+    }
+    Ok(())
+}
+
+fn xsm_write_string<E: ByteOrder>(body: &mut Vec<u8>, text: &str) -> Result<(), XsmError> {
+    body.write_i32::<E>(text.chars().count() as i32)?;
+    for character in text.chars() {
+        body.write_u8(character as u8)?;
+    }
+    Ok(())
+}
+
+fn xsm_write_vec3d<E: ByteOrder>(body: &mut Vec<u8>, vec3d: &XsmVec3d) -> Result<(), XsmError> {
+    body.write_f32::<E>(vec3d.x)?;
+    body.write_f32::<E>(vec3d.y)?;
+    body.write_f32::<E>(vec3d.z)?;
+    Ok(())
+}
+
+/// Writes a rotation in the encoding the chunk expects, converting it when needed.
+fn xsm_write_rotation<E: ByteOrder>(
+    body: &mut Vec<u8>,
+    rotation: &XsmRotation,
+    compressed: bool,
+) -> Result<(), XsmError> {
+    let rotation = match (rotation, compressed) {
+        (XsmRotation::Uncompressed(_), true) => {
+            XsmRotation::Compressed(XsmQuaternion16::from_quaternion(rotation.to_quaternion()))
+        }
+        (XsmRotation::Compressed(_), false) => XsmRotation::Uncompressed(rotation.to_quaternion()),
+        _ => *rotation,
+    };
+    match rotation {
+        XsmRotation::Compressed(quaternion) => {
+            body.write_i16::<E>(quaternion.x)?;
+            body.write_i16::<E>(quaternion.y)?;
+            body.write_i16::<E>(quaternion.z)?;
+            body.write_i16::<E>(quaternion.w)?;
+        }
+        XsmRotation::Uncompressed(quaternion) => {
+            body.write_f32::<E>(quaternion.x)?;
+            body.write_f32::<E>(quaternion.y)?;
+            body.write_f32::<E>(quaternion.z)?;
+            body.write_f32::<E>(quaternion.w)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_metadata<E: ByteOrder>(body: &mut Vec<u8>, xsm: &Xsm) -> Result<(), XsmError> {
+    body.write_f32::<E>(xsm.metadata.unused)?;
+    body.write_f32::<E>(xsm.metadata.max_acceptable_error)?;
+    body.write_i32::<E>(xsm.metadata.fps)?;
+    body.write_u8(xsm.metadata.exporter_major_version)?;
+    body.write_u8(xsm.metadata.exporter_minor_version)?;
+    body.write_u8(0)?; //Padding
+    body.write_u8(0)?; //Padding
+    xsm_write_string::<E>(body, &xsm.metadata.source_app)?;
+    xsm_write_string::<E>(body, &xsm.metadata.original_filename)?;
+    xsm_write_string::<E>(body, &xsm.metadata.export_date)?;
+    xsm_write_string::<E>(body, &xsm.metadata.motion_name)?;
+    Ok(())
+}
+
+fn write_submotion<E: ByteOrder>(
+    body: &mut Vec<u8>,
+    submotion: &XsmSubMotion,
+    compressed: bool,
+) -> Result<(), XsmError> {
+    xsm_write_rotation::<E>(body, &submotion.pose_rot, compressed)?;
+    xsm_write_rotation::<E>(body, &submotion.bind_pose_rot, compressed)?;
+    xsm_write_rotation::<E>(body, &submotion.pose_scale_rot, compressed)?;
+    xsm_write_rotation::<E>(body, &submotion.bind_pose_scale_rot, compressed)?;
+    xsm_write_vec3d::<E>(body, &submotion.pose_pos)?;
+    xsm_write_vec3d::<E>(body, &submotion.pose_scale)?;
+    xsm_write_vec3d::<E>(body, &submotion.bind_pose_pos)?;
+    xsm_write_vec3d::<E>(body, &submotion.bind_pose_scale_pos)?;
+    body.write_i32::<E>(submotion.pos_key.len() as i32)?;
+    body.write_i32::<E>(submotion.rot_key.len() as i32)?;
+    body.write_i32::<E>(submotion.scale_key.len() as i32)?;
+    body.write_i32::<E>(submotion.scale_rot_key.len() as i32)?;
+    if compressed {
+        body.write_f32::<E>(submotion.max_error)?;
+    }
+    xsm_write_string::<E>(body, &submotion.node_name)?;
+    for key in submotion.pos_key.iter() {
+        xsm_write_vec3d::<E>(body, &key.pos)?;
+        body.write_f32::<E>(key.time)?;
+    }
+    for key in submotion.rot_key.iter() {
+        xsm_write_rotation::<E>(body, &key.rot, compressed)?;
+        body.write_f32::<E>(key.time)?;
+    }
+    for key in submotion.scale_key.iter() {
+        xsm_write_vec3d::<E>(body, &key.scale)?;
+        body.write_f32::<E>(key.time)?;
+    }
+    for key in submotion.scale_rot_key.iter() {
+        xsm_write_rotation::<E>(body, &key.rot, compressed)?;
+        body.write_f32::<E>(key.time)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use byteorder::{BigEndian, LittleEndian};
+
+    use crate::xsm::xsm_fixtures::{file, motion, submotion};
+    use crate::xsm::xsm_structs::{Xsm, XsmRotation};
+
+    fn assert_round_trip(bytes: &[u8]) -> Xsm {
+        let xsm = Xsm::from_bytes(bytes).unwrap();
+        assert_eq!(xsm.to_bytes().unwrap(), bytes);
+        xsm
+    }
+
+    #[test]
+    fn little_endian_files_round_trip() {
+        for version in 1..=3 {
+            let xsm = assert_round_trip(&file::<LittleEndian>(false, version, 1));
+            assert_eq!(xsm.bone_animation.skeletal_submotion.len(), 3);
+            assert_eq!(xsm.chunks[1].data, vec![1, 2, 3, 4, 5]);
+        }
+    }
+
+    #[test]
+    fn big_endian_files_round_trip() {
+        for version in 1..=3 {
+            let xsm = assert_round_trip(&file::<BigEndian>(true, version, 1));
+            assert_eq!(xsm.metadata.fps, 30);
+            assert_eq!(
+                xsm.bone_animation.skeletal_submotion[2].node_name,
+                "Bip01 Head"
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_rotation_encoding_per_chunk() {
+        let xsm = assert_round_trip(&file::<LittleEndian>(false, 1, 1));
+        let submotions = &xsm.bone_animation.skeletal_submotion;
+        assert!(matches!(
+            submotions[0].rot_key[0].rot,
+            XsmRotation::Uncompressed(_)
+        ));
+        assert!(matches!(
+            submotions[1].rot_key[0].rot,
+            XsmRotation::Compressed(_)
+        ));
+        assert_eq!(xsm.metadata.unused, 1.0);
+    }
+
+    #[test]
+    fn motions_without_chunks_use_default_layout() {
+        let xsm = motion(&["Bip01", "Bip01 Spine"], 4);
+        let bytes = xsm.to_bytes().unwrap();
+        let read = assert_round_trip(&bytes);
+        assert_eq!(read.chunks.len(), 2);
+        assert_eq!(read.bone_animation.skeletal_submotion[1].pos_key.len(), 4);
+    }
+
+    fn chunk_types(xsm: &Xsm) -> Vec<i32> {
+        xsm.chunks.iter().map(|chunk| chunk.chunk_type).collect()
+    }
+
+    fn node_names(xsm: &Xsm) -> Vec<&str> {
+        xsm.bone_animation
+            .skeletal_submotion
+            .iter()
+            .map(|submotion| submotion.node_name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn removed_bones_leave_no_empty_chunks() {
+        let mut xsm = Xsm::from_bytes(&file::<LittleEndian>(false, 1, 1)).unwrap();
+        xsm.bone_animation.skeletal_submotion.remove(0);
+        let read = Xsm::from_bytes(&xsm.to_bytes().unwrap()).unwrap();
+        assert_eq!(node_names(&read), vec!["Bip01 Spine", "Bip01 Head"]);
+        assert_eq!(chunk_types(&read), vec![201, 50, 202]);
+    }
+
+    #[test]
+    fn added_bones_are_written() {
+        let mut xsm = Xsm::from_bytes(&file::<LittleEndian>(false, 1, 1)).unwrap();
+        xsm.bone_animation.skeletal_submotion.truncate(1);
+        let mut uncompressed = xsm.bone_animation.skeletal_submotion[0].clone();
+        uncompressed.node_name = "Bip01 Pelvis".to_string();
+        xsm.bone_animation.skeletal_submotion.push(uncompressed);
+        xsm.bone_animation
+            .skeletal_submotion
+            .push(submotion("Bip01 Tail", 3));
+        let read = Xsm::from_bytes(&xsm.to_bytes().unwrap()).unwrap();
+        assert_eq!(
+            node_names(&read),
+            vec!["Bip01", "Bip01 Pelvis", "Bip01 Tail"]
+        );
+        assert_eq!(chunk_types(&read), vec![201, 50, 200, 200, 202]);
+        assert_eq!(read.bone_animation.skeletal_submotion[2].pos_key.len(), 3);
+    }
+
+    #[test]
+    fn keeps_submotion_chunks_with_unknown_versions() {
+        let bytes = file::<LittleEndian>(false, 1, 2);
+        let mut xsm = assert_round_trip(&bytes);
+        assert_eq!(node_names(&xsm), vec!["Bip01 Spine", "Bip01 Head"]);
+        xsm.bone_animation.skeletal_submotion.pop();
+        let read = Xsm::from_bytes(&xsm.to_bytes().unwrap()).unwrap();
+        assert_eq!(chunk_types(&read), vec![201, 50, 200, 202]);
+        assert_eq!(read.chunks[2].data, xsm.chunks[2].data);
+        assert_eq!(node_names(&read), vec!["Bip01 Spine"]);
+    }
+}
@@ -0,0 +1,3 @@
+pub(crate) mod xsmtime_enums;
+pub(crate) mod xsmtime_parser;
+pub(crate) mod xsmtime_structs;
@@ -0,0 +1,60 @@
+#![allow(dead_code)]
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) enum XsmTimeEventType {
+    Hit,
+    Sound,
+    Effect,
+    Other(String),
+}
+
+#[derive(Debug)]
+pub enum XsmTimeError {
+    Io(std::io::Error),
+    Xml(quick_xml::Error),
+    InvalidTime { element: String, value: String },
+}
+
+impl fmt::Display for XsmTimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            XsmTimeError::Io(error) => write!(f, "I/O error: {}", error),
+            XsmTimeError::Xml(error) => write!(f, "XML error: {}", error),
+            XsmTimeError::InvalidTime { element, value } => {
+                write!(f, "Element {} has an invalid time {:?}", element, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for XsmTimeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            XsmTimeError::Io(error) => Some(error),
+            XsmTimeError::Xml(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for XsmTimeError {
+    fn from(error: std::io::Error) -> Self {
+        XsmTimeError::Io(error)
+    }
+}
+
+impl From<quick_xml::Error> for XsmTimeError {
+    fn from(error: quick_xml::Error) -> Self {
+        XsmTimeError::Xml(error)
+    }
+}
+
+impl From<quick_xml::events::attributes::AttrError> for XsmTimeError {
+    fn from(error: quick_xml::events::attributes::AttrError) -> Self {
+        XsmTimeError::Xml(error.into())
+    }
+}
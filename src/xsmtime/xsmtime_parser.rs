@@ -0,0 +1,189 @@
+#![allow(dead_code)]
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::xsm::xsm_structs::Xsm;
+use crate::xsmtime::xsmtime_enums::{XsmTimeError, XsmTimeEventType};
+use crate::xsmtime::xsmtime_structs::{XsmTime, XsmTimeEvent};
+
+pub fn xsmtimeparse(path: &str) -> Result<XsmTime, XsmTimeError> {
+    let xsmtime_file = File::open(path)?;
+    XsmTime::from_reader(BufReader::new(xsmtime_file))
+}
+
+impl XsmTime {
+    /// Reads every element that carries a `time` attribute as an event, whatever its
+    /// nesting. Elements without one are only containers and are skipped.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<XsmTime, XsmTimeError> {
+        let mut reader = Reader::from_reader(reader);
+        reader.trim_text(true);
+        let mut xsmtime = XsmTime { events: vec![] };
+        let mut buffer = vec![];
+        loop {
+            match reader.read_event_into(&mut buffer)? {
+                Event::Start(element) | Event::Empty(element) => {
+                    if let Some(event) = read_event(&reader, &element)? {
+                        xsmtime.events.push(event);
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+            buffer.clear();
+        }
+        xsmtime.events.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Ok(xsmtime)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<XsmTime, XsmTimeError> {
+        XsmTime::from_reader(bytes)
+    }
+
+    /// Events with `start <= time < end`.
+    pub(crate) fn events_between(&self, start: f32, end: f32) -> &[XsmTimeEvent] {
+        let first = self.events.partition_point(|event| event.time < start);
+        let last = self.events.partition_point(|event| event.time < end);
+        &self.events[first..last.max(first)]
+    }
+}
+
+/// Splits an event name into lowercase words at separators, case changes and digits,
+/// so `SND_Swing` gives `snd`, `swing` and `HitEffect` gives `hit`, `effect`.
+fn name_tokens(name: &str) -> Vec<String> {
+    let chars: Vec<char> = name.chars().collect();
+    let mut tokens = vec![];
+    let mut token = String::new();
+    for (index, &c) in chars.iter().enumerate() {
+        if !c.is_ascii_alphanumeric() {
+            if !token.is_empty() {
+                tokens.push(std::mem::take(&mut token));
+            }
+            continue;
+        }
+        if !token.is_empty() {
+            let previous = chars[index - 1];
+            let next_is_lower =
+                matches!(chars.get(index + 1), Some(next) if next.is_ascii_lowercase());
+            let boundary = (previous.is_ascii_lowercase() && c.is_ascii_uppercase())
+                || (previous.is_ascii_uppercase() && c.is_ascii_uppercase() && next_is_lower)
+                || (previous.is_ascii_digit() != c.is_ascii_digit());
+            if boundary {
+                tokens.push(std::mem::take(&mut token));
+            }
+        }
+        token.push(c.to_ascii_lowercase());
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+    tokens
+}
+
+/// Classifies an event by its `type` attribute, or by its element name when it has none.
+/// The first word of the name that is a known event kind decides; anything else is kept
+/// as `Other`.
+fn event_type(element: &str, attributes: &[(String, String)]) -> XsmTimeEventType {
+    let kind = attributes
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("type"))
+        .map(|(_, value)| value.as_str())
+        .unwrap_or(element);
+    name_tokens(kind)
+        .iter()
+        .find_map(|token| match token.as_str() {
+            "hit" | "attack" | "damage" => Some(XsmTimeEventType::Hit),
+            "sound" | "snd" | "se" | "sfx" => Some(XsmTimeEventType::Sound),
+            "effect" | "eft" | "fx" | "vfx" => Some(XsmTimeEventType::Effect),
+            _ => None,
+        })
+        .unwrap_or_else(|| XsmTimeEventType::Other(kind.to_string()))
+}
+
+fn read_event<R>(
+    reader: &Reader<R>,
+    element: &BytesStart,
+) -> Result<Option<XsmTimeEvent>, XsmTimeError> {
+    let name = String::from_utf8_lossy(element.name().as_ref()).to_string();
+    let mut attributes = vec![];
+    for attribute in element.attributes() {
+        let attribute = attribute?;
+        attributes.push((
+            String::from_utf8_lossy(attribute.key.as_ref()).to_string(),
+            attribute.decode_and_unescape_value(reader)?.to_string(),
+        ));
+    }
+    let Some((_, time)) = attributes
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("time"))
+    else {
+        return Ok(None);
+    };
+    let time = time
+        .trim()
+        .parse::<f32>()
+        .map_err(|_| XsmTimeError::InvalidTime {
+            element: name.clone(),
+            value: time.clone(),
+        })?;
+    Ok(Some(XsmTimeEvent {
+        time,
+        event_type: event_type(&name, &attributes),
+        element: name,
+        attributes,
+    }))
+}
+
+impl Xsm {
+    /// Adds the events of an `.xsmtime` file to the motion, kept sorted by time.
+    pub(crate) fn attach_events(&mut self, xsmtime: &XsmTime) {
+        self.events.extend(xsmtime.events.iter().cloned());
+        self.events.sort_by(|a, b| a.time.total_cmp(&b.time));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &[u8] = include_bytes!("../../tests/fixtures/skill_attack.xsmtime");
+
+    #[test]
+    fn splits_names_into_words() {
+        assert_eq!(name_tokens("LeftFootStep"), vec!["left", "foot", "step"]);
+        assert_eq!(name_tokens("SND_Swing"), vec!["snd", "swing"]);
+        assert_eq!(name_tokens("VFXHit2"), vec!["vfx", "hit", "2"]);
+    }
+
+    #[test]
+    fn parses_fixture_sorted_by_time() {
+        let xsmtime = XsmTime::from_bytes(FIXTURE).unwrap();
+        let events: Vec<(f32, XsmTimeEventType)> = xsmtime
+            .events
+            .iter()
+            .map(|event| (event.time, event.event_type.clone()))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (0.1, XsmTimeEventType::Sound),
+                (0.25, XsmTimeEventType::Other("LeftFootStep".to_string())),
+                (0.3, XsmTimeEventType::Effect),
+                (0.4, XsmTimeEventType::Hit),
+                (0.45, XsmTimeEventType::Hit),
+                (0.6, XsmTimeEventType::Other("WhiteFlash".to_string())),
+                (0.7, XsmTimeEventType::Other("camera_shake".to_string())),
+            ]
+        );
+        assert_eq!(xsmtime.events_between(0.25, 0.45).len(), 3);
+    }
+
+    #[test]
+    fn rejects_invalid_times() {
+        let result = XsmTime::from_bytes(b"<Event Time=\"soon\" Type=\"Hit\"/>");
+        assert!(matches!(result, Err(XsmTimeError::InvalidTime { .. })));
+    }
+}
@@ -0,0 +1,18 @@
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+
+use crate::xsmtime::xsmtime_enums::XsmTimeEventType;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct XsmTime {
+    pub(crate) events: Vec<XsmTimeEvent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct XsmTimeEvent {
+    pub(crate) time: f32,
+    pub(crate) event_type: XsmTimeEventType,
+    pub(crate) element: String,
+    pub(crate) attributes: Vec<(String, String)>,
+}